@@ -1,52 +1,64 @@
 use std::fmt::Debug;
 
+use dyn_std::Instance;
+
 use crate::directive::{DirectiveFactory as Directive, Node, Element};
 use crate::language::{Context, Expr, RuntimeError, SyntaxError};
 use crate::reader::{Reader, TagInfo};
 use crate::writer::render;
 
+use super::Slot;
+
 /// Application directive.
 /// 
 /// ### Example
-/// Apply function `NAME` with `PARAMS`.
+/// Apply function `NAME` with `ARGS`. Arguments can be passed
+/// either by position or by name (e.g. `f(a, size = 'lg')`).
+/// 
+/// ```yfelo
+/// {@apply NAME(ARGS)}
+/// ```
+/// 
+/// ### Example
+/// Apply function `NAME` with `ARGS` and block content.
+/// The content is rendered in the current scope and passed to the function
+/// as `children`, while named slots are passed as properties of `slots`.
+/// These are only bound for a block, and cannot be parameters of the function.
 /// 
 /// ```yfelo
-/// {@apply NAME(PARAMS)}
+/// {#apply NAME(ARGS)}
+///     ...
+/// {:slot SLOT}
+///     ...
+/// {/apply}
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct Apply {
-    name: String,
-    args: Vec<Box<dyn Expr>>,
+    expr: Box<dyn Expr>,
+    block: bool,
 }
 
 impl Directive for Apply {
     fn open(reader: &mut Reader, info: &TagInfo) -> Result<Self, SyntaxError> {
-        let name = reader.parse_ident()?.0.into();
-        let args = if let Ok(_) = reader.parse_punct("(") {
-            let mut args = vec![];
-            loop {
-                if let Ok(_) = reader.parse_punct(")") {
-                    break;
-                }
-                args.push(reader.parse_expr()?);
-                if let Ok(_) = reader.parse_punct(",") {
-                    continue;
-                }
-                reader.parse_punct(")")?;
-                break;
-            }
-            args
-        } else {
-            vec![]
-        };
-        info.expect_empty()?;
-        Ok(Self { name, args })
+        let expr = reader.parse_expr()?;
+        Ok(Self { expr, block: info.mark == '#' })
     }
 
-    fn render(&self, ctx: &mut dyn Context, nodes: &[Node], _: &[Element]) -> Result<String, Box<dyn RuntimeError>> {
-        let value = ctx.apply(&self.name, self.args.clone(), &mut |ctx| {
-            render(ctx, nodes)
-        })?;
-        Ok(value.to_string()?)
+    fn render(&self, ctx: &mut dyn Context, nodes: &[Node], branches: &[Element]) -> Result<String, Box<dyn RuntimeError>> {
+        let mut slots = vec![];
+        if self.block {
+            let mut fork = ctx.fork();
+            slots.push(("children".into(), render(fork.as_mut(), nodes)?));
+            for branch in branches {
+                if let Some(instance) = branch.directive.as_any().downcast_ref::<Instance<Slot, ()>>() {
+                    let mut fork = ctx.fork();
+                    slots.push((instance.0.name.clone(), render(fork.as_mut(), &branch.nodes)?));
+                } else {
+                    panic!("unexpected directive instance: {:?}", branch.directive)
+                }
+            }
+        }
+        let value = ctx.apply(self.expr.as_ref(), slots)?;
+        Ok(ctx.stringify(value.as_ref())?)
     }
 }
@@ -0,0 +1,37 @@
+use std::fmt::Debug;
+
+use crate::directive::{DirectiveFactory as Directive, Node, Element};
+use crate::language::{Context, Pattern, RuntimeError, SyntaxError};
+use crate::reader::{Reader, TagInfo};
+use crate::writer::render;
+
+/// Capture directive.
+/// 
+/// ### Example
+/// Render the block content and bind the resulting string to `PAT`.
+/// 
+/// ```yfelo
+/// {#capture PAT}
+///     ...
+/// {/capture}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pat: Box<dyn Pattern>,
+}
+
+impl Directive for Capture {
+    fn open(reader: &mut Reader, info: &TagInfo) -> Result<Self, SyntaxError> {
+        info.expect_children()?;
+        let pat = reader.parse_pattern()?;
+        Ok(Self { pat })
+    }
+
+    fn render(&self, ctx: &mut dyn Context, nodes: &[Node], _: &[Element]) -> Result<String, Box<dyn RuntimeError>> {
+        let mut fork = ctx.fork();
+        let output = render(fork.as_mut(), nodes)?;
+        let value = ctx.new_string(output);
+        ctx.bind(self.pat.as_ref(), value)?;
+        Ok(String::new())
+    }
+}
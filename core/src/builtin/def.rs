@@ -59,6 +59,9 @@ impl DefVar {
 ///     ...
 /// {/def}
 /// ```
+/// 
+/// Each parameter may have a default value (`PAT = EXPR`),
+/// and the last one may collect extra arguments (`...NAME`).
 #[derive(Debug, Clone, PartialEq)]
 pub struct DefFn {
     ident: String,
@@ -95,14 +98,22 @@ impl Directive for Def {
                 if let Ok(_) = reader.parse_punct(")") {
                     break;
                 }
-                let pat = reader.parse_pattern()?;
+                let pat = reader.parse_param()?;
                 let expr = if let Ok(_) = reader.parse_punct("=") {
                     Some(reader.parse_expr()?)
                 } else {
                     None
                 };
+                let rest = pat.is_rest();
                 params.push((pat, expr));
                 if let Ok(_) = reader.parse_punct(",") {
+                    if rest {
+                        reader.parse_punct(")").map_err(|error| SyntaxError {
+                            message: "rest parameter must be last".into(),
+                            ..error
+                        })?;
+                        break;
+                    }
                     continue;
                 }
                 reader.parse_punct(")")?;
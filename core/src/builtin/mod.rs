@@ -1,11 +1,17 @@
 mod r#apply;
+mod r#capture;
 mod r#def;
 mod r#for;
 mod r#if;
+mod r#set;
+mod r#slot;
 mod r#stub;
 
 pub use r#apply::*;
+pub use r#capture::*;
 pub use r#def::*;
 pub use r#for::*;
 pub use r#if::*;
+pub use r#set::*;
+pub use r#slot::*;
 pub use r#stub::*;
@@ -0,0 +1,38 @@
+use std::fmt::Debug;
+
+use crate::directive::{DirectiveFactory as Directive, Node, Element};
+use crate::language::{Context, Expr, Pattern, RuntimeError, SyntaxError};
+use crate::reader::{Reader, TagInfo};
+
+/// Assignment directive.
+/// 
+/// Unlike `{@def}`, which always introduces new bindings in the current scope,
+/// `{@set}` updates the nearest enclosing bindings of `PAT`.
+/// 
+/// ### Example
+/// Assign the result of `EXPR` to `PAT`.
+/// 
+/// ```yfelo
+/// {@set PAT = EXPR}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Set {
+    pat: Box<dyn Pattern>,
+    expr: Box<dyn Expr>,
+}
+
+impl Directive for Set {
+    fn open(reader: &mut Reader, info: &TagInfo) -> Result<Self, SyntaxError> {
+        info.expect_empty()?;
+        let pat = reader.parse_pattern()?;
+        reader.parse_punct("=")?;
+        let expr = reader.parse_expr()?;
+        Ok(Self { pat, expr })
+    }
+
+    fn render(&self, ctx: &mut dyn Context, _: &[Node], _: &[Element]) -> Result<String, Box<dyn RuntimeError>> {
+        let value = ctx.eval(self.expr.as_ref())?;
+        ctx.assign(self.pat.as_ref(), value)?;
+        Ok(String::new())
+    }
+}
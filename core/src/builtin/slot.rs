@@ -0,0 +1,33 @@
+use std::fmt::Debug;
+
+use crate::directive::{DirectiveFactory as Directive, Node, Element};
+use crate::language::{Context, RuntimeError, SyntaxError};
+use crate::reader::{Reader, TagInfo};
+use crate::writer::render;
+
+/// Named slot of an application directive.
+/// 
+/// ### Example
+/// ```yfelo
+/// {#apply NAME(PARAMS)}
+///     ...
+/// {:slot SLOT}
+///     ...
+/// {/apply}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+    pub name: String,
+}
+
+impl Directive for Slot {
+    fn open(reader: &mut Reader, _: &TagInfo) -> Result<Self, SyntaxError> {
+        let name = reader.parse_ident()?.0.into();
+        Ok(Self { name })
+    }
+
+    fn render(&self, ctx: &mut dyn Context, nodes: &[Node], _: &[Element]) -> Result<String, Box<dyn RuntimeError>> {
+        let mut fork = ctx.fork();
+        render(fork.as_mut(), nodes)
+    }
+}
@@ -10,6 +10,23 @@ pub struct SyntaxError {
     pub range: (usize, usize),
 }
 
+/// Error of a language parsing an expression or a pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The input does not start with an expression or a pattern,
+    /// so the reader reports what it expected at this position.
+    Incomplete(SyntaxError),
+    /// The input starts with a well-formed expression or pattern
+    /// whose content is invalid, such as an unknown escape sequence.
+    Invalid(SyntaxError),
+}
+
+impl From<SyntaxError> for ParseError {
+    fn from(error: SyntaxError) -> Self {
+        ParseError::Invalid(error)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Error {
     Syntax(SyntaxError),
@@ -18,8 +35,8 @@ pub enum Error {
 
 #[dyn_trait]
 pub trait Language<#[dynamic] E: Expr, #[dynamic] P: Pattern> {
-    fn parse_expr(input: &str, offset: usize) -> Result<(E, usize), SyntaxError>;
-    fn parse_pattern(input: &str, offset: usize) -> Result<(P, usize), SyntaxError>;
+    fn parse_expr(input: &str, offset: usize) -> Result<(E, usize), ParseError>;
+    fn parse_pattern(input: &str, offset: usize) -> Result<(P, usize), ParseError>;
 }
 
 #[dyn_trait]
@@ -28,6 +45,9 @@ pub trait Expr: Debug + Clone + PartialEq {}
 #[dyn_trait]
 pub trait Pattern: Debug + Clone + PartialEq {
     fn into_ident(self) -> Option<String>;
+    /// Whether the pattern collects remaining arguments (`...NAME`),
+    /// which is only valid as the last parameter of a function.
+    fn is_rest(&self) -> bool;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -40,14 +60,18 @@ pub enum Definition {
 pub trait Context<#[dynamic] E: Expr, #[dynamic] P: Pattern, #[dynamic] V: Value<R>, #[dynamic] R: RuntimeError> {
     fn eval(&self, expr: &E) -> Result<V, R>;
     fn fork(&self) -> Self;
+    fn new_string(&self, value: String) -> V;
+    /// Convert a value to output, which may depend on options of the context
+    /// such as number formats.
+    fn stringify(&self, value: &V) -> Result<String, R>;
     fn bind(&mut self, pattern: &P, value: V) -> Result<(), R>;
+    fn assign(&mut self, pattern: &P, value: V) -> Result<(), R>;
     fn def(&mut self, name: &str, params: Vec<(P, Option<E>)>, definition: Definition) -> Result<(), R>;
-    fn apply(&self, name: &str, args: Vec<E>, init: &mut dyn FnMut(&mut dyn Context) -> Result<String, Box<dyn RuntimeError>>) -> Result<V, R>;
+    fn apply(&self, expr: &E, slots: Vec<(String, String)>) -> Result<V, R>;
 }
 
 #[dyn_trait]
 pub trait Value<#[dynamic] R: RuntimeError>: Debug + Clone + PartialEq {
-    fn to_string(&self) -> Result<String, R>;
     fn as_bool(&self) -> Result<bool, R>;
     fn as_entries(&self) -> Result<Vec<(Self, Self)>, R>;
 }
@@ -56,12 +80,12 @@ pub trait Value<#[dynamic] R: RuntimeError>: Debug + Clone + PartialEq {
 pub trait RuntimeError: Debug + Clone {}
 
 impl<E: ExprFactory, P: PatternFactory, T: LanguageFactory<E, P>> Language for PhantomData<(T, E, P)> {
-    fn parse_expr(&self, input: &str, offset: usize) -> Result<(Box<dyn Expr>, usize), SyntaxError> {
+    fn parse_expr(&self, input: &str, offset: usize) -> Result<(Box<dyn Expr>, usize), ParseError> {
         let (a, b) = <T as LanguageFactory<E, P>>::parse_expr(input, offset)?;
         Ok((Box::new(Instance::new(a)), b))
     }
 
-    fn parse_pattern(&self, input: &str, offset: usize) -> Result<(Box<dyn Pattern>, usize), SyntaxError> {
+    fn parse_pattern(&self, input: &str, offset: usize) -> Result<(Box<dyn Pattern>, usize), ParseError> {
         let (a, b) = <T as LanguageFactory<E, P>>::parse_pattern(input, offset)?;
         Ok((Box::new(Instance::new(a)), b))
     }
@@ -4,7 +4,7 @@ extern crate dyn_derive;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 
-use builtin::{Apply, Def, For, If, Stub};
+use builtin::{Apply, Capture, Def, For, If, Set, Slot, Stub};
 use reader::Reader;
 
 pub use directive::*;
@@ -28,11 +28,14 @@ impl Yfelo {
     pub fn new() -> Self {
         let mut dirs: HashMap<String, Box<dyn Directive>> = HashMap::new();
         dirs.insert("apply".into(), Box::new(PhantomData::<Apply>));
+        dirs.insert("apply:slot".into(), Box::new(PhantomData::<Slot>));
+        dirs.insert("capture".into(), Box::new(PhantomData::<Capture>));
         dirs.insert("def".into(), Box::new(PhantomData::<Def>));
         dirs.insert("if".into(), Box::new(PhantomData::<If>));
         dirs.insert("if:else".into(), Box::new(PhantomData::<Stub>));
         dirs.insert("if:elif".into(), Box::new(PhantomData::<If>));
         dirs.insert("for".into(), Box::new(PhantomData::<For>));
+        dirs.insert("set".into(), Box::new(PhantomData::<Set>));
         Self {
             dirs,
             langs: HashMap::new(),
@@ -5,7 +5,7 @@ use dyn_std::Instance;
 
 use crate::builtin::Stub;
 use crate::directive::Directive;
-use crate::language::{Expr, Language, Pattern, SyntaxError};
+use crate::language::{Expr, Language, ParseError, Pattern, SyntaxError};
 use crate::{Element, MetaSyntax, Node};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -111,27 +111,43 @@ impl<'i> Reader<'i> {
                 self.trim_start();
                 Ok(expr)
             },
-            Err(_) => Err(SyntaxError {
+            Err(ParseError::Invalid(e)) => Err(e),
+            Err(ParseError::Incomplete(_)) => Err(SyntaxError {
                 message: format!("expect expression"),
                 range: (self.offset, self.offset),
             }),
         }
     }
 
-    pub fn parse_pattern(&mut self) -> Result<Box<dyn Pattern>, SyntaxError> {
+    /// Parse a function parameter, which may also be a rest pattern.
+    pub fn parse_param(&mut self) -> Result<Box<dyn Pattern>, SyntaxError> {
         match self.lang.parse_pattern(self.input, self.offset) {
-            Ok((expr, offset)) => {
+            Ok((pat, offset)) => {
                 self.skip(offset);
                 self.trim_start();
-                Ok(expr)
+                Ok(pat)
             },
-            Err(_) => Err(SyntaxError {
+            Err(ParseError::Invalid(e)) => Err(e),
+            Err(ParseError::Incomplete(_)) => Err(SyntaxError {
                 message: format!("expect pattern"),
                 range: (self.offset, self.offset),
             }),
         }
     }
 
+    pub fn parse_pattern(&mut self) -> Result<Box<dyn Pattern>, SyntaxError> {
+        let (input, start) = (self.input, self.offset);
+        let pat = self.parse_param()?;
+        if pat.is_rest() {
+            let source = input[..self.offset - start].trim_end();
+            return Err(SyntaxError {
+                message: format!("unexpected rest pattern '{}'", source),
+                range: (start, start + source.len()),
+            });
+        }
+        Ok(pat)
+    }
+
     pub fn parse_ident(&mut self) -> Result<(&'i str, (usize, usize)), SyntaxError> {
         let pos = self.input
             .find(|c: char| !c.is_ascii_alphanumeric())
@@ -0,0 +1,126 @@
+use std::fmt;
+use std::rc::Rc;
+
+use super::{I18n, RuntimeError};
+
+/// Configuration of a context, shared with all of its forks.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Make `&&` and `||` return the operand that decides the result
+    /// instead of a boolean, so that `title || 'Untitled'` works like in JavaScript.
+    pub logical_operand: bool,
+    /// How numbers are formatted when interpolated.
+    pub number: NumberFormat,
+    /// Locale and message catalogs for the `t` function.
+    pub i18n: I18n,
+    /// How reading an undefined variable, a missing object property
+    /// or an out-of-range index is handled.
+    pub undefined: Undefined,
+}
+
+/// How reading an undefined variable, a missing object property
+/// or an out-of-range index of an array or a string is handled.
+///
+/// Both sides of `?.` and the left operand of `??` may always be undefined.
+#[derive(Clone, Default)]
+pub enum Undefined {
+    /// Evaluate to `null`, which renders as nothing.
+    #[default]
+    Null,
+    /// Evaluate to `null` and pass a warning to the hook.
+    Warn(Rc<dyn Fn(&RuntimeError)>),
+    /// Raise a runtime error.
+    Error,
+}
+
+impl fmt::Debug for Undefined {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "Null"),
+            Self::Warn(_) => write!(f, "Warn(..)"),
+            Self::Error => write!(f, "Error"),
+        }
+    }
+}
+
+/// Locale-independent options for formatting numbers.
+/// 
+/// The default options format numbers in their shortest exact form.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NumberFormat {
+    /// Fixed number of digits after the decimal point.
+    /// Integers are left untouched unless `percent` or `scientific` is set.
+    /// Values above [`NumberFormat::MAX_DECIMALS`] are capped.
+    pub decimals: Option<usize>,
+    /// Separator inserted between groups of three digits in the integral part.
+    pub thousands: Option<char>,
+    /// Minimum width of the output, padded with spaces on the left.
+    pub width: usize,
+    /// Pad with zeros after the sign instead of spaces.
+    pub zero_pad: bool,
+    /// Multiply by 100 and append a `%` sign.
+    pub percent: bool,
+    /// Use scientific notation, such as `1.5e3`.
+    pub scientific: bool,
+}
+
+impl NumberFormat {
+    /// Largest supported number of digits after the decimal point.
+    pub const MAX_DECIMALS: usize = 100;
+
+    pub fn format_integer(&self, n: i64) -> String {
+        if self.percent || self.scientific {
+            return self.format_number(n as f64);
+        }
+        self.pad(self.group(n.to_string()))
+    }
+
+    pub fn format_number(&self, mut n: f64) -> String {
+        if self.percent {
+            n *= 100.;
+        }
+        let decimals = self.decimals.map(|d| d.min(Self::MAX_DECIMALS));
+        let output = match (self.scientific, decimals) {
+            (true, Some(d)) => format!("{:.*e}", d, n),
+            (true, None) => format!("{:e}", n),
+            (false, Some(d)) => self.group(format!("{:.*}", d, n)),
+            (false, None) => self.group(format!("{}", n)),
+        };
+        match self.percent {
+            true => self.pad(output + "%"),
+            false => self.pad(output),
+        }
+    }
+
+    fn group(&self, digits: String) -> String {
+        let Some(sep) = self.thousands else {
+            return digits;
+        };
+        let (sign, digits) = match digits.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", digits.as_str()),
+        };
+        let end = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+        let mut output = sign.to_string();
+        for (i, c) in digits[..end].chars().enumerate() {
+            if i > 0 && (end - i) % 3 == 0 {
+                output.push(sep);
+            }
+            output.push(c);
+        }
+        output + &digits[end..]
+    }
+
+    fn pad(&self, output: String) -> String {
+        let len = output.chars().count();
+        if len >= self.width {
+            return output;
+        }
+        let fill = self.width - len;
+        match (self.zero_pad, output.strip_prefix('-')) {
+            (true, Some(rest)) => format!("-{}{}", "0".repeat(fill), rest),
+            (true, None) => format!("{}{}", "0".repeat(fill), output),
+            (false, _) => format!("{}{}", " ".repeat(fill), output),
+        }
+    }
+}
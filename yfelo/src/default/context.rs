@@ -2,29 +2,54 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::rc::{Rc, Weak};
 
 use dyn_std::Instance;
+use regex::Regex;
 use yfelo_core::{factory, writer::render, ContextFactory, Definition};
 
-use super::{Expr, Pattern, RuntimeError, Value};
+use super::{stdlib, BinaryOp, Config, Expr, Pattern, RuntimeError, Undefined, Value};
 
+/// The number of compiled regular expressions a context keeps,
+/// after which the cache is cleared so that dynamic patterns cannot grow it without limit.
+pub const MAX_REGEXES: usize = 256;
+
+/// A single scope of bindings.
+/// 
+/// Functions hold the scope they are defined in, so that they are evaluated
+/// with lexical scoping even after they are returned from that scope.
 #[derive(Default)]
-struct ContextInner {
-    parent: Weak<ContextInner>,
+pub struct ContextInner {
+    parent: Option<Rc<ContextInner>>,
     store: RefCell<HashMap<String, Rc<Value>>>,
+    config: Rc<Config>,
+    /// Compiled regular expressions, shared with all forks.
+    /// At most [`MAX_REGEXES`] are kept.
+    regexes: Rc<RefCell<HashMap<String, Regex>>>,
+    /// Scopes held by functions, shared with all forks.
+    /// 
+    /// A function stored in the scope it holds forms a reference cycle,
+    /// which is broken by clearing these scopes when the root context is dropped.
+    captured: Rc<RefCell<Vec<Weak<ContextInner>>>>,
+}
+
+impl fmt::Debug for ContextInner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ContextInner").finish_non_exhaustive()
+    }
 }
 
 impl ContextInner {
-    fn get(self: &Rc<Self>, key: &str) -> Value {
+    fn get(self: &Rc<Self>, key: &str) -> Option<Value> {
         let mut this = Some(self.clone());
         while let Some(inner) = this {
             if let Some(v) = inner.store.borrow().get(key) {
-                return Value::Ref(v.clone())
+                return Some(Value::Ref(v.clone()))
             }
-            this = inner.parent.upgrade();
+            this = inner.parent.clone();
         }
-        Value::Null
+        stdlib::global(key).map(Value::Native)
     }
 
     fn set(&self, key: String, value: Value) -> Result<(), RuntimeError> {
@@ -38,28 +63,143 @@ impl ContextInner {
             }),
         }
     }
-}
 
-fn iter_option<T: Clone>(vec: impl IntoIterator<Item = T>) -> impl Iterator<Item = Option<T>> {
-    vec.into_iter().map(Some).chain(std::iter::repeat(None))
+    fn assign(self: &Rc<Self>, key: String, value: Value) -> Result<(), RuntimeError> {
+        let mut this = Some(self.clone());
+        while let Some(inner) = this {
+            if let Some(v) = inner.store.borrow_mut().get_mut(&key) {
+                *v = value.into_rc();
+                return Ok(())
+            }
+            this = inner.parent.clone();
+        }
+        Err(RuntimeError {
+            message: format!("'{}' is not defined", key),
+        })
+    }
+
+    fn fork(self: &Rc<Self>) -> Rc<Self> {
+        Rc::new(Self {
+            parent: Some(self.clone()),
+            store: Default::default(),
+            config: self.config.clone(),
+            regexes: self.regexes.clone(),
+            captured: self.captured.clone(),
+        })
+    }
+
+    /// Create a function that holds this scope.
+    fn capture(self: &Rc<Self>, params: Vec<(Pattern, Option<Expr>)>, definition: Definition) -> Value {
+        let mut captured = self.captured.borrow_mut();
+        if captured.last().is_none_or(|scope| scope.as_ptr() != Rc::as_ptr(self)) {
+            if captured.len() == captured.capacity() {
+                captured.retain(|scope| scope.strong_count() > 0);
+            }
+            captured.push(Rc::downgrade(self));
+        }
+        Value::Abs(params, definition, self.clone())
+    }
 }
 
 #[derive(Default)]
 pub struct Context(Rc<ContextInner>);
 
+impl Drop for Context {
+    fn drop(&mut self) {
+        if self.0.parent.is_some() {
+            return;
+        }
+        // cycles can only pass through captured scopes and their ancestors
+        let captured = std::mem::take(&mut *self.0.captured.borrow_mut());
+        for scope in captured {
+            let mut this = scope.upgrade();
+            while let Some(inner) = this {
+                inner.store.borrow_mut().clear();
+                this = inner.parent.clone();
+            }
+        }
+    }
+}
+
 impl Context {
     pub fn new() -> Self {
         Self::default()
     }
 
-    fn preapply(&self, params: &Vec<(Pattern, Option<Expr>)>, args: Vec<Value>) -> Result<Self, RuntimeError> {
-        let mut ctx = self.fork();
-        if args.len() > params.len() {
+    pub fn with_config(config: Config) -> Self {
+        Self(Rc::new(ContextInner {
+            config: Rc::new(config),
+            ..Default::default()
+        }))
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.0.config
+    }
+
+    /// Compile a regular expression, or reuse it if it has been compiled recently.
+    pub fn regex(&self, pattern: &str) -> Result<Regex, RuntimeError> {
+        if let Some(regex) = self.0.regexes.borrow().get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Regex::new(pattern).map_err(|error| {
+            // syntax errors span several lines to point at the pattern, and end with the reason
+            let error = error.to_string();
+            let reason = error.lines().last().unwrap_or_default();
+            RuntimeError {
+                message: format!("invalid regex '{}': {}", pattern, reason.trim_start_matches("error: ")),
+            }
+        })?;
+        let mut regexes = self.0.regexes.borrow_mut();
+        if regexes.len() >= MAX_REGEXES {
+            regexes.clear();
+        }
+        regexes.insert(pattern.into(), regex.clone());
+        Ok(regex)
+    }
+
+    fn preapply(self, params: &Vec<(Pattern, Option<Expr>)>, args: Vec<(Option<String>, Value)>) -> Result<Self, RuntimeError> {
+        let mut ctx = self;
+        let (params, rest) = match params.split_last() {
+            Some(((Pattern::Rest(ident, _), _), params)) => (params, Some(ident)),
+            _ => (params.as_slice(), None),
+        };
+        if args.iter().skip_while(|(name, _)| name.is_none()).any(|(name, _)| name.is_none()) {
+            return Err(RuntimeError {
+                message: "positional argument follows named argument".into(),
+            });
+        }
+        let (positional, named): (Vec<_>, Vec<_>) = args.into_iter().partition(|(name, _)| name.is_none());
+        if rest.is_none() && positional.len() > params.len() {
             return Err(RuntimeError {
-                message: format!("expect {} arguments, found {}", params.len(), args.len()),
+                message: format!("expect {} arguments, found {}", params.len(), positional.len()),
             });
         }
-        for ((pattern, default), arg) in params.iter().zip(iter_option(args)) {
+        let mut values = vec![None; params.len()];
+        let mut extra = vec![];
+        for (i, (_, value)) in positional.into_iter().enumerate() {
+            match values.get_mut(i) {
+                Some(slot) => *slot = Some(value),
+                None => extra.push(value.into_rc()),
+            }
+        }
+        for (name, value) in named {
+            let name = name.unwrap();
+            let Some(i) = params.iter().position(|(pattern, _)| {
+                matches!(pattern, Pattern::Ident(ident, _) if *ident == name)
+            }) else {
+                return Err(RuntimeError {
+                    message: format!("unknown argument '{}'", name),
+                });
+            };
+            if values[i].is_some() {
+                return Err(RuntimeError {
+                    message: format!("duplicate argument '{}'", name),
+                });
+            }
+            values[i] = Some(value);
+        }
+        for ((pattern, default), arg) in params.iter().zip(values) {
             let value = match arg {
                 Some(value) => value,
                 None => match default {
@@ -73,9 +213,84 @@ impl Context {
             };
             ctx.bind(pattern, value)?;
         }
+        if let Some(ident) = rest {
+            ctx.0.set(ident.clone(), Value::Array(extra))?;
+        }
         Ok(ctx)
     }
 
+    /// Bind `value` to `pattern` in the current scope,
+    /// or update the nearest enclosing bindings if `assign` is set.
+    fn destruct(&mut self, pattern: &Pattern, value: Value, assign: bool) -> Result<(), RuntimeError> {
+        match pattern {
+            Pattern::Ident(ident, _) => {
+                if assign {
+                    self.0.assign(ident.into(), value)?;
+                } else {
+                    self.0.set(ident.into(), value)?;
+                }
+            },
+            Pattern::Rest(ident, _) => {
+                return Err(RuntimeError {
+                    message: format!("unexpected rest pattern '...{}'", ident),
+                });
+            },
+            Pattern::Array(pats, _) => {
+                let mut values = value.into_array()?.into_iter();
+                for (i, pattern) in pats.iter().enumerate() {
+                    if let (Pattern::Rest(ident, range), true) = (pattern, i + 1 == pats.len()) {
+                        let rest = Value::Array(values.by_ref().collect());
+                        self.destruct(&Pattern::Ident(ident.clone(), *range), rest, assign)?;
+                        break;
+                    }
+                    let value = match values.next() {
+                        Some(rc) => Value::from_rc(rc),
+                        _ => Value::Null,
+                    };
+                    self.destruct(pattern, value, assign)?;
+                }
+            },
+            Pattern::Object(entries, _) => {
+                let map = value.into_object()?;
+                let mut keys = vec![];
+                for (i, (key, pattern)) in entries.iter().enumerate() {
+                    if let (Some(Pattern::Rest(ident, range)), true) = (pattern, i + 1 == entries.len()) {
+                        let rest = map.iter()
+                            .filter(|(k, _)| !keys.contains(*k))
+                            .map(|(k, v)| (k.clone(), v.clone()))
+                            .collect();
+                        self.destruct(&Pattern::Ident(ident.clone(), *range), Value::Object(rest), assign)?;
+                        break;
+                    }
+                    let mut pattern = Cow::Borrowed(pattern);
+                    let key = match key {
+                        Expr::Ident(key, _) => {
+                            if let None = pattern.as_ref() {
+                                pattern = Cow::Owned(Some(Pattern::Ident(key.clone(), None)));
+                            }
+                            key.clone()
+                        },
+                        expr => self.eval(expr)?.as_string()?,
+                    };
+                    let value = match map.get(&key) {
+                        Some(value) => Value::from_rc(value.clone()),
+                        None => Value::Null,
+                    };
+                    self.destruct(pattern.as_ref().as_ref().unwrap(), value, assign)?;
+                    keys.push(key);
+                }
+            },
+            Pattern::Default(pattern, expr, _) => {
+                let value = match value.deref() {
+                    Value::Null => self.eval(expr)?,
+                    _ => value,
+                };
+                self.destruct(pattern, value, assign)?;
+            },
+        }
+        Ok(())
+    }
+
     fn postapply(self, definition: &Definition) -> Result<Value, RuntimeError> {
         match definition {
             Definition::Inline(expr) => {
@@ -90,51 +305,213 @@ impl Context {
         }
     }
 
-    fn apply(&self, f: &Value, args: Vec<Expr>, init: &mut dyn FnMut(&mut dyn yfelo_core::Context) -> Result<String, Box<dyn yfelo_core::RuntimeError>>) -> Result<Value, RuntimeError> {
-        let (params, definition) = f.as_abs()?;
-        let args = args.iter()
-            .map(|expr| self.eval(expr))
-            .collect::<Result<Vec<_>, _>>()?;
-        let mut inst = Instance::new(self.preapply(params, args)?);
-        init(&mut inst).map_err(|e| {
-            e.as_any_box().downcast::<Instance<RuntimeError, ()>>().unwrap().0
-        })?;
-        inst.0.postapply(definition)
+    /// Evaluate a chain of calls and property accesses,
+    /// returning `None` if it is short-circuited by optional chaining.
+    /// 
+    /// A `lenient` chain may end with an undefined variable or property,
+    /// such as the left operand of `?.` or `??`.
+    fn eval_chain(&self, expr: &Expr, lenient: bool) -> Result<Option<Value>, RuntimeError> {
+        Ok(match expr {
+            Expr::Optional(expr, _) => match self.eval_chain(expr, true)? {
+                Some(value) if !matches!(value.deref(), Value::Null) => Some(value),
+                _ => None,
+            },
+            Expr::Index(lhs, rhs, _, range) => match self.eval_chain(lhs, false)? {
+                Some(value) => {
+                    // properties accessed with `?.` may be undefined
+                    let lenient = lenient || matches!(lhs.as_ref(), Expr::Optional(..));
+                    Some(self.index(&value, &self.eval(rhs)?, *range, lenient)?)
+                },
+                None => None,
+            },
+            Expr::Slice(lhs, start, end, _) => match self.eval_chain(lhs, false)? {
+                Some(lhs) => {
+                    let start = start.as_ref().map(|expr| self.eval(expr)).transpose()?;
+                    let end = end.as_ref().map(|expr| self.eval(expr)).transpose()?;
+                    Some(lhs.slice(start.as_ref(), end.as_ref())?)
+                },
+                None => None,
+            },
+            Expr::Apply(func, args, _) => match self.eval_callee(func)? {
+                Some((func, this)) => Some(self.apply(&func, this, args, vec![])?),
+                None => None,
+            },
+            Expr::Ident(ident, range) => Some(self.lookup(ident, *range, lenient)?),
+            expr => Some(self.eval(expr)?),
+        })
+    }
+
+    fn lookup(&self, ident: &str, range: Option<(usize, usize)>, lenient: bool) -> Result<Value, RuntimeError> {
+        match ident {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "null" => Ok(Value::Null),
+            _ => match self.0.get(ident) {
+                Some(value) => Ok(value),
+                None if lenient => Ok(Value::Null),
+                None => self.undefined(format!("'{}' is not defined", ident), range),
+            },
+        }
+    }
+
+    fn index(&self, lhs: &Value, key: &Value, range: Option<(usize, usize)>, lenient: bool) -> Result<Value, RuntimeError> {
+        let value = lhs.get(key)?;
+        match (lhs.deref(), &value) {
+            (_, Value::Null) if lenient => Ok(value),
+            (Value::Object(_), Value::Null) => self.undefined(format!("'{}' is not defined", key.as_string()?), range),
+            (Value::Array(_) | Value::String(_), Value::Null) => {
+                self.undefined(format!("index {} is out of range", key.as_integer()?), range)
+            },
+            _ => Ok(value),
+        }
+    }
+
+    /// Handle a read of an undefined variable, property or index according to [`Config::undefined`].
+    fn undefined(&self, message: String, range: Option<(usize, usize)>) -> Result<Value, RuntimeError> {
+        let error = RuntimeError {
+            message: match range {
+                Some((start, end)) => format!("{} at {}..{}", message, start, end),
+                None => message,
+            },
+        };
+        match &self.0.config.undefined {
+            Undefined::Null => Ok(Value::Null),
+            Undefined::Warn(hook) => {
+                hook(&error);
+                Ok(Value::Null)
+            },
+            Undefined::Error => Err(error),
+        }
+    }
+
+    fn preslot(&mut self, params: &[(Pattern, Option<Expr>)], slots: Vec<(String, String)>) -> Result<(), RuntimeError> {
+        if slots.is_empty() {
+            return Ok(())
+        }
+        for name in ["children", "slots"] {
+            if params.iter().any(|(pattern, _)| pattern.binds(name)) {
+                return Err(RuntimeError {
+                    message: format!("parameter '{}' conflicts with block content", name),
+                });
+            }
+        }
+        let mut map = BTreeMap::new();
+        for (name, output) in slots {
+            if map.contains_key(&name) {
+                return Err(RuntimeError {
+                    message: format!("duplicate slot '{}'", name),
+                });
+            }
+            map.insert(name, Value::String(output).into_rc());
+        }
+        let children = map.get("children").cloned().unwrap_or_else(|| Value::String(String::new()).into_rc());
+        self.0.set("children".into(), Value::from_rc(children))?;
+        self.0.set("slots".into(), Value::Object(map))?;
+        Ok(())
+    }
+
+    /// Evaluate the function of a call, along with the receiver of a method call.
+    /// 
+    /// A method is looked up in the built-in method table when the receiver lacks a property of that name.
+    fn eval_callee(&self, func: &Expr) -> Result<Option<(Value, Option<Value>)>, RuntimeError> {
+        let Expr::Index(lhs, key, false, _) = func else {
+            return Ok(self.eval_chain(func, false)?.map(|func| (func, None)));
+        };
+        let Expr::String(name, _) = key.as_ref() else {
+            unreachable!("unexpected key: {:?}", key)
+        };
+        let Some(lhs) = self.eval_chain(lhs, false)? else {
+            return Ok(None);
+        };
+        if let Some(method) = stdlib::method(&lhs, name) {
+            return Ok(Some((Value::Native(method), Some(lhs))));
+        }
+        match lhs.deref() {
+            Value::Object(map) if map.contains_key(name) => Ok(Some((lhs.get(&Value::String(name.clone()))?, None))),
+            _ => Err(RuntimeError {
+                message: format!("{} has no method '{}'", lhs.type_name(), name),
+            }),
+        }
+    }
+
+    fn apply(&self, f: &Value, this: Option<Value>, args: &[(Option<String>, Expr)], slots: Vec<(String, String)>) -> Result<Value, RuntimeError> {
+        let mut values = this.into_iter().map(|this| (None, this)).collect::<Vec<_>>();
+        for (name, expr) in args {
+            let Expr::Spread(expr, _) = expr else {
+                values.push((name.clone(), self.eval(expr)?));
+                continue;
+            };
+            // arrays are spread as positional arguments, objects as named arguments
+            match self.eval(expr)?.deref() {
+                Value::Array(vec) => {
+                    values.extend(vec.iter().map(|rc| (None, Value::from_rc(rc.clone()))));
+                },
+                Value::Object(map) => {
+                    values.extend(map.iter().map(|(k, v)| (Some(k.clone()), Value::from_rc(v.clone()))));
+                },
+                value => return Err(RuntimeError {
+                    message: format!("expect array or object, found {}", value.type_name()),
+                }),
+            }
+        }
+        self.invoke(f, values, slots)
+    }
+
+    /// Call a function with positional arguments, such as a callback passed to a built-in.
+    pub fn call(&self, f: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        self.invoke(f, args.into_iter().map(|value| (None, value)).collect(), vec![])
+    }
+
+    fn invoke(&self, f: &Value, values: Vec<(Option<String>, Value)>, slots: Vec<(String, String)>) -> Result<Value, RuntimeError> {
+        if let Value::Native(native) = f.deref() {
+            if let Some((Some(name), _)) = values.iter().find(|(name, _)| name.is_some()) {
+                return Err(RuntimeError {
+                    message: format!("unknown argument '{}'", name),
+                });
+            }
+            return native(self, values.into_iter().map(|(_, value)| value).collect());
+        }
+        let (params, definition, scope) = f.as_abs()?;
+        let mut ctx = Self(scope.fork());
+        ctx.preslot(params, slots)?;
+        let ctx = ctx.preapply(params, values)?;
+        ctx.postapply(definition)
     }
 }
 
 impl factory::Context<Expr, Pattern, Value, RuntimeError> for Context {
     fn eval(&self, expr: &Expr) -> Result<Value, RuntimeError> {
         Ok(match expr {
+            Expr::Integer(n, _) => Value::Integer(*n),
             Expr::Number(n, _) => Value::Number(*n),
             Expr::String(s, _) => Value::String(s.clone()),
-            Expr::Ident(ident, _) => {
-                if ident == "true" {
-                    Value::Bool(true)
-                } else if ident == "false" {
-                    Value::Bool(false)
-                } else if ident == "null" {
-                    Value::Null
-                } else {
-                    self.0.get(ident)
-                }
-            },
+            Expr::Ident(ident, range) => self.lookup(ident, *range, false)?,
             Expr::Array(vec, _) => {
-                Value::Array(vec.iter().map(|expr| {
-                    self.eval(expr).map(Value::into_rc)
-                }).collect::<Result<Vec<_>, _>>()?)
+                let mut array = vec![];
+                for expr in vec {
+                    match expr {
+                        Expr::Spread(expr, _) => array.extend(self.eval(expr)?.into_array()?),
+                        expr => array.push(self.eval(expr)?.into_rc()),
+                    }
+                }
+                Value::Array(array)
             },
             Expr::Object(entries, _) => {
                 let mut map = BTreeMap::new();
                 for (key, value) in entries {
+                    if let Expr::Spread(expr, _) = key {
+                        map.extend(self.eval(expr)?.into_object()?);
+                        continue;
+                    }
                     let mut value = match value {
                         Some(expr) => Some(self.eval(expr)?),
                         None => None,
                     };
+                    // identifiers are literal keys, and shorthands if there is no value
                     let key = match key {
-                        Expr::Ident(key, _) => {
+                        Expr::Ident(key, range) => {
                             if let None = value {
-                                value = Some(self.0.get(&key));
+                                value = Some(self.lookup(key, *range, false)?);
                             }
                             key.clone()
                         },
@@ -144,78 +521,96 @@ impl factory::Context<Expr, Pattern, Value, RuntimeError> for Context {
                 }
                 Value::Object(map)
             },
-            Expr::Apply(func, args, _) => {
-                let func = self.eval(func)?;
-                // fixme
-                self.apply(&func, args.clone(), &mut |_| Ok(String::new()))?
+            Expr::Apply(..) | Expr::Index(..) | Expr::Slice(..) | Expr::Optional(..) => {
+                self.eval_chain(expr, false)?.unwrap_or(Value::Null)
+            },
+            Expr::Computed(expr, _) => self.eval(expr)?,
+            Expr::Template(parts, _) => {
+                let mut output = String::new();
+                for part in parts {
+                    output += &self.eval(part)?.format(&self.0.config.number);
+                }
+                Value::String(output)
+            },
+            Expr::Cond(cond, then, other, _) => {
+                if self.eval(cond)?.as_bool()? {
+                    self.eval(then)?
+                } else {
+                    self.eval(other)?
+                }
             },
             Expr::Unary(op, expr, _) => {
                 let value = self.eval(expr)?;
                 op.eval(value)?
             },
-            Expr::Binary(lhs, op, rhs, _) => {
+            Expr::Binary(lhs, BinaryOp::Nullish, rhs, _) => {
+                let lhs = self.eval_chain(lhs, true)?.unwrap_or(Value::Null);
+                match lhs.deref() {
+                    Value::Null => self.eval(rhs)?,
+                    _ => lhs,
+                }
+            },
+            Expr::Binary(lhs, op @ (BinaryOp::And | BinaryOp::Or), rhs, _) => {
                 let lhs = self.eval(lhs)?;
-                let rhs = self.eval(rhs)?;
-                op.eval(lhs, rhs)?
+                let value = if lhs.as_bool()? == (*op == BinaryOp::Or) {
+                    lhs
+                } else {
+                    self.eval(rhs)?
+                };
+                match self.0.config.logical_operand {
+                    true => value,
+                    false => Value::Bool(value.as_bool()?),
+                }
             },
-            Expr::Index(lhs, rhs, _, _) => {
+            Expr::Binary(lhs, op, rhs, _) => {
                 let lhs = self.eval(lhs)?;
                 let rhs = self.eval(rhs)?;
-                lhs.get(&rhs)?
+                op.eval(lhs, rhs, &self.0.config.number)?
+            },
+            Expr::Spread(_, _) => {
+                return Err(RuntimeError {
+                    message: "unexpected spread expression".into(),
+                });
+            },
+            Expr::Lambda(params, body, _) => {
+                let definition = Definition::Inline(Box::new(Instance::new(body.as_ref().clone())));
+                self.0.capture(params.clone(), definition)
             },
         })
     }
 
     fn fork(&self) -> Self {
-        Self(Rc::new(ContextInner {
-            parent: Rc::downgrade(&self.0),
-            store: Default::default(),
-        }))
+        Self(self.0.fork())
+    }
+
+    fn new_string(&self, value: String) -> Value {
+        Value::String(value)
+    }
+
+    fn stringify(&self, value: &Value) -> Result<String, RuntimeError> {
+        Ok(value.format(&self.0.config.number))
     }
 
     fn bind(&mut self, pattern: &Pattern, value: Value) -> Result<(), RuntimeError> {
-        match pattern {
-            Pattern::Ident(ident, _) => {
-                self.0.set(ident.into(), value)?;
-            },
-            Pattern::Array(pats, _) => {
-                for (pattern, value) in pats.iter().zip(iter_option(value.into_array()?)) {
-                    self.bind(pattern, match value {
-                        Some(rc) => Value::from_rc(rc),
-                        _ => Value::Null,
-                    })?;
-                }
-            },
-            Pattern::Object(entries, _) => {
-                let map = value.into_object()?;
-                for (key, pattern) in entries {
-                    let mut pattern = Cow::Borrowed(pattern);
-                    let key = match key {
-                        Expr::Ident(key, _) => {
-                            if let None = pattern.as_ref() {
-                                pattern = Cow::Owned(Some(Pattern::Ident(key.clone(), None)));
-                            }
-                            key.clone()
-                        },
-                        expr => self.eval(expr)?.as_string()?,
-                    };
-                    let value = match map.get(&key) {
-                        Some(value) => Value::from_rc(value.clone()),
-                        None => Value::Null,
-                    };
-                    self.bind(pattern.as_ref().as_ref().unwrap(), value)?;
-                }
-            },
-        }
-        Ok(())
+        self.destruct(pattern, value, false)
+    }
+
+    fn assign(&mut self, pattern: &Pattern, value: Value) -> Result<(), RuntimeError> {
+        self.destruct(pattern, value, true)
     }
 
     fn def(&mut self, name: &str, params: Vec<(Pattern, Option<Expr>)>, definition: Definition) -> Result<(), RuntimeError> {
-        self.0.set(name.into(), Value::Abs(params, definition))?;
+        self.0.set(name.into(), self.0.capture(params, definition))?;
         Ok(())
     }
 
-    fn apply(&self, name: &str, args: Vec<Expr>, init: &mut dyn FnMut(&mut dyn yfelo_core::Context) -> Result<String, Box<dyn yfelo_core::RuntimeError>>) -> Result<Value, RuntimeError> {
-        Self::apply(&self, &self.0.get(name), args, init)
+    fn apply(&self, expr: &Expr, slots: Vec<(String, String)>) -> Result<Value, RuntimeError> {
+        match expr {
+            Expr::Apply(func, args, _) => match self.eval_callee(func)? {
+                Some((func, this)) => Self::apply(self, &func, this, args, slots),
+                None => Ok(Value::Null),
+            },
+            expr => Self::apply(self, &self.eval(expr)?, None, &[], slots),
+        }
     }
 }
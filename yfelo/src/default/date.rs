@@ -0,0 +1,299 @@
+use std::fmt;
+
+use super::RuntimeError;
+
+const MS_PER_MINUTE: i64 = 60_000;
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// The largest supported distance from the Unix epoch, which is 100,000,000 days as in JavaScript.
+pub const MAX_TIMESTAMP: i64 = 100_000_000 * MS_PER_DAY;
+
+/// The largest supported UTC offset in minutes, exclusive.
+const MAX_OFFSET: i32 = 24 * 60;
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+const WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
+/// An instant in time with a fixed UTC offset.
+///
+/// Only fixed offsets are supported, so no timezone database is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    timestamp: i64,
+    offset: i32,
+}
+
+/// The calendar fields of a date-time in its own offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fields {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millis: u32,
+    /// Day of the week, where `0` is Sunday.
+    pub weekday: u32,
+    /// Day of the year, starting from `1`.
+    pub ordinal: u32,
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Format a year with at least four digits. As in ISO-8601, years before 0
+/// and after 9999 are signed, so that they can be parsed back.
+fn format_year(year: i64) -> String {
+    match year {
+        0..=9999 => format!("{:04}", year),
+        _ if year < 0 => format!("-{:04}", -year),
+        _ => format!("+{:04}", year),
+    }
+}
+
+/// Days since the Unix epoch of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// A cursor over the input of [`DateTime::parse`].
+struct Cursor<'i>(&'i str);
+
+impl Cursor<'_> {
+    fn digits(&mut self, len: usize) -> Option<u32> {
+        let digits = self.0.get(..len).filter(|s| s.bytes().all(|b| b.is_ascii_digit()))?;
+        self.0 = &self.0[len..];
+        digits.parse().ok()
+    }
+
+    fn eat(&mut self, prefix: &[char]) -> Option<char> {
+        let c = self.0.chars().next().filter(|c| prefix.contains(c))?;
+        self.0 = &self.0[c.len_utf8()..];
+        Some(c)
+    }
+}
+
+impl DateTime {
+    /// Create a date-time from milliseconds since the Unix epoch and an offset from UTC in minutes.
+    /// 
+    /// The timestamp is limited to [`MAX_TIMESTAMP`] and the offset to less than a day in either direction.
+    pub fn new(timestamp: i64, offset: i32) -> Result<Self, RuntimeError> {
+        if !(1 - MAX_OFFSET..MAX_OFFSET).contains(&offset) {
+            return Err(RuntimeError {
+                message: format!("invalid offset '{}'", offset),
+            });
+        }
+        if !(-MAX_TIMESTAMP..=MAX_TIMESTAMP).contains(&timestamp) {
+            return Err(RuntimeError {
+                message: "date out of range".into(),
+            });
+        }
+        Ok(Self { timestamp, offset })
+    }
+
+    /// Milliseconds since the Unix epoch.
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// Offset from UTC in minutes.
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// Parse an ISO-8601 date or date-time, such as `2024-02-29`, `2024-02-29T12:30:00Z`
+    /// or `2024-02-29 12:30:00.250+08:00`. A missing offset means UTC.
+    /// Years outside `0000` to `9999` need a sign, such as `-0001` or `+10000`.
+    pub fn parse(input: &str) -> Result<Self, RuntimeError> {
+        Self::parse_fields(input.trim()).ok_or_else(|| RuntimeError {
+            message: format!("invalid date '{}'", input),
+        })
+    }
+
+    fn parse_fields(input: &str) -> Option<Self> {
+        let mut cursor = Cursor(input);
+        // signed years may have up to six digits, which covers the supported range
+        let year = match cursor.eat(&['-', '+']) {
+            Some(sign) => {
+                let len = cursor.0.bytes().take_while(u8::is_ascii_digit).count();
+                let year = cursor.digits(len).filter(|_| (4..=6).contains(&len))? as i64;
+                if sign == '-' { -year } else { year }
+            },
+            None => cursor.digits(4)? as i64,
+        };
+        cursor.eat(&['-'])?;
+        let month = cursor.digits(2).filter(|m| (1..=12).contains(m))?;
+        cursor.eat(&['-'])?;
+        let day = cursor.digits(2).filter(|d| (1..=days_in_month(year, month)).contains(d))?;
+        let (mut hour, mut minute, mut second, mut millis) = (0, 0, 0, 0);
+        if cursor.eat(&['T', 't', ' ']).is_some() {
+            hour = cursor.digits(2).filter(|h| *h < 24)?;
+            cursor.eat(&[':'])?;
+            minute = cursor.digits(2).filter(|m| *m < 60)?;
+            if cursor.eat(&[':']).is_some() {
+                second = cursor.digits(2).filter(|s| *s < 60)?;
+                if cursor.eat(&['.', ',']).is_some() {
+                    let len = cursor.0.bytes().take_while(u8::is_ascii_digit).count();
+                    let fraction = &cursor.0[..len];
+                    millis = format!("{:0<3}", fraction.get(..3).unwrap_or(fraction)).parse().ok()?;
+                    cursor.0 = &cursor.0[len..];
+                }
+            }
+        }
+        let offset = match cursor.eat(&['Z', 'z', '+', '-']) {
+            None | Some('Z' | 'z') => 0,
+            Some(sign) => {
+                let hours = cursor.digits(2).filter(|h| *h < 24)?;
+                cursor.eat(&[':']);
+                let minutes = cursor.digits(2).filter(|m| *m < 60)?;
+                let offset = (hours * 60 + minutes) as i32;
+                if sign == '-' { -offset } else { offset }
+            },
+        };
+        if !cursor.0.is_empty() {
+            return None;
+        }
+        let local = days_from_civil(year, month, day) * MS_PER_DAY
+            + (hour * 3_600_000 + minute * 60_000 + second * 1000 + millis) as i64;
+        Self::new(local - offset as i64 * MS_PER_MINUTE, offset).ok()
+    }
+
+    /// Parse a UTC offset such as `+08:00`, `-0530` or `Z`.
+    pub fn parse_offset(input: &str) -> Result<i32, RuntimeError> {
+        Self::parse_fields(&format!("1970-01-01T00:00{}", input))
+            .filter(|_| !input.is_empty())
+            .map(|date| date.offset)
+            .ok_or_else(|| RuntimeError {
+                message: format!("invalid offset '{}'", input),
+            })
+    }
+
+    pub fn fields(&self) -> Fields {
+        let local = self.timestamp + self.offset as i64 * MS_PER_MINUTE;
+        let days = local.div_euclid(MS_PER_DAY);
+        let ms = local.rem_euclid(MS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        Fields {
+            year,
+            month,
+            day,
+            hour: ms / 3_600_000,
+            minute: ms / 60_000 % 60,
+            second: ms / 1000 % 60,
+            millis: ms % 1000,
+            weekday: (days + 4).rem_euclid(7) as u32,
+            ordinal: (days - days_from_civil(year, 1, 1) + 1) as u32,
+        }
+    }
+
+    /// The same instant in another offset.
+    pub fn with_offset(&self, offset: i32) -> Result<Self, RuntimeError> {
+        Self::new(self.timestamp, offset)
+    }
+
+    /// Format with `strftime`-style specifiers:
+    ///
+    /// - `%Y` `%y` `%m` `%d` `%e` `%j`: year, two-digit year, month, day, space-padded day, day of year
+    /// - `%H` `%I` `%M` `%S` `%f` `%p`: hour, 12-hour clock hour, minute, second, milliseconds, AM/PM
+    /// - `%B` `%b` `%A` `%a` `%u` `%w`: month and weekday names, ISO and Sunday-based weekday numbers
+    /// - `%z` `%:z` `%s` `%F` `%T` `%%`: offset, offset with colon, Unix seconds, `%Y-%m-%d`, `%H:%M:%S`, `%`
+    pub fn format(&self, pattern: &str) -> Result<String, RuntimeError> {
+        let f = self.fields();
+        let mut output = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+            let spec = match chars.next() {
+                Some(':') => chars.next().filter(|c| *c == 'z').map(|_| ":z"),
+                Some(c) => "YymdejHIMSfpBbAauwzsFT%".find(c).map(|i| &"YymdejHIMSfpBbAauwzsFT%"[i..=i]),
+                None => None,
+            };
+            output += &match spec {
+                Some("Y") => format_year(f.year),
+                Some("y") => format!("{:02}", f.year.rem_euclid(100)),
+                Some("m") => format!("{:02}", f.month),
+                Some("d") => format!("{:02}", f.day),
+                Some("e") => format!("{:2}", f.day),
+                Some("j") => format!("{:03}", f.ordinal),
+                Some("H") => format!("{:02}", f.hour),
+                Some("I") => format!("{:02}", (f.hour + 11) % 12 + 1),
+                Some("M") => format!("{:02}", f.minute),
+                Some("S") => format!("{:02}", f.second),
+                Some("f") => format!("{:03}", f.millis),
+                Some("p") => (if f.hour < 12 { "AM" } else { "PM" }).to_string(),
+                Some("B") => MONTHS[f.month as usize - 1].to_string(),
+                Some("b") => MONTHS[f.month as usize - 1][..3].to_string(),
+                Some("A") => WEEKDAYS[f.weekday as usize].to_string(),
+                Some("a") => WEEKDAYS[f.weekday as usize][..3].to_string(),
+                Some("u") => ((f.weekday + 6) % 7 + 1).to_string(),
+                Some("w") => f.weekday.to_string(),
+                Some("z") => self.format_offset(""),
+                Some(":z") => self.format_offset(":"),
+                Some("s") => self.timestamp.div_euclid(1000).to_string(),
+                Some("F") => format!("{}-{:02}-{:02}", format_year(f.year), f.month, f.day),
+                Some("T") => format!("{:02}:{:02}:{:02}", f.hour, f.minute, f.second),
+                Some("%") => "%".to_string(),
+                _ => return Err(RuntimeError {
+                    message: format!("invalid date format '{}'", pattern),
+                }),
+            };
+        }
+        Ok(output)
+    }
+
+    fn format_offset(&self, sep: &str) -> String {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.unsigned_abs();
+        format!("{}{:02}{}{:02}", sign, offset / 60, sep, offset % 60)
+    }
+}
+
+/// Format as ISO-8601, with milliseconds only if there are any.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = self.fields();
+        let pattern = match (fields.millis, self.offset) {
+            (0, 0) => "%FT%TZ",
+            (0, _) => "%FT%T%:z",
+            (_, 0) => "%FT%T.%fZ",
+            (_, _) => "%FT%T.%f%:z",
+        };
+        write!(f, "{}", self.format(pattern).unwrap())
+    }
+}
@@ -1,167 +1,282 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 
 use pest::{iterators::{Pair, Pairs}, Parser};
-use yfelo_core::{factory, SyntaxError};
+use yfelo_core::{factory, ParseError, SyntaxError};
 
 use super::operator::{BinaryOp, UnaryOp};
 use super::parser::{DefaultParser, Rule, ToRange};
+use super::Pattern;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
+    Integer(i64, Option<(usize, usize)>),
     Number(f64, Option<(usize, usize)>),
     String(String, Option<(usize, usize)>),
     Ident(String, Option<(usize, usize)>),
     Array(Vec<Expr>, Option<(usize, usize)>),
     Object(Vec<(Expr, Option<Expr>)>, Option<(usize, usize)>),
-    Apply(Box<Expr>, Vec<Expr>, Option<(usize, usize)>),
+    Apply(Box<Expr>, Vec<(Option<String>, Expr)>, Option<(usize, usize)>),
     Unary(UnaryOp, Box<Expr>, Option<(usize, usize)>),
     Binary(Box<Expr>, BinaryOp, Box<Expr>, Option<(usize, usize)>),
     Index(Box<Expr>, Box<Expr>, bool, Option<(usize, usize)>),
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>, Option<(usize, usize)>),
+    Lambda(Vec<(Pattern, Option<Expr>)>, Box<Expr>, Option<(usize, usize)>),
+    Spread(Box<Expr>, Option<(usize, usize)>),
+    Optional(Box<Expr>, Option<(usize, usize)>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>, Option<(usize, usize)>),
+    Template(Vec<Expr>, Option<(usize, usize)>),
+    Computed(Box<Expr>, Option<(usize, usize)>),
 }
 
 impl factory::Expr for Expr {}
 
 macro_rules! left_assoc {
     ($curr:ident, $inner:ident) => {
-        fn $curr(pair: Pair<Rule>, offset: usize) -> Self {
+        fn $curr(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
             let mut pairs = pair.into_inner();
-            let mut expr = Expr::$inner(pairs.next().unwrap(), offset);
+            let mut expr = Expr::$inner(pairs.next().unwrap(), offset)?;
             while pairs.len() > 0 {
                 let pair = pairs.next().unwrap();
                 let op = BinaryOp::from(pair.as_str());
-                let rhs = Expr::$inner(pairs.next().unwrap(), offset);
+                let rhs = Expr::$inner(pairs.next().unwrap(), offset)?;
                 expr = Expr::Binary(Box::new(expr), op, Box::new(rhs), Some(pair.to_range(offset)));
             }
-            expr
+            Ok(expr)
         }
     };
 }
 
 impl Expr {
-    pub fn parse(input: &str, offset: usize) -> Result<(Expr, usize), SyntaxError> {
+    pub fn parse(input: &str, offset: usize) -> Result<(Expr, usize), ParseError> {
         match DefaultParser::parse(Rule::expr, input) {
             Ok(pairs) => {
                 let len = pairs.as_str().len();
-                Ok((Expr::from(pairs.into_iter().next().unwrap(), offset), len))
+                Ok((Expr::from(pairs.into_iter().next().unwrap(), offset)?, len))
             },
-            Err(e) => Err(SyntaxError {
+            Err(e) => Err(ParseError::Incomplete(SyntaxError {
                 message: e.to_string(),
                 range: e.location.to_range(offset),
-            }),
+            })),
         }
     }
 
-    fn from(pair: Pair<Rule>, offset: usize) -> Self {
+    fn from(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
         assert!(matches!(pair.as_rule(), Rule::expr));
-        Expr::from_or(pair.into_inner().next().unwrap(), offset)
+        let pair = pair.into_inner().next().unwrap();
+        match pair.as_rule() {
+            Rule::lambda => Expr::from_lambda(pair, offset),
+            _ => Expr::from_cond(pair, offset),
+        }
     }
 
-    fn from_list(pairs: Pairs<Rule>, offset: usize) -> Vec<Self> {
+    fn from_cond(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
+        let range = Some(pair.to_range(offset));
+        let mut pairs = pair.into_inner();
+        let cond = Expr::from_nullish(pairs.next().unwrap(), offset)?;
+        Ok(match pairs.next() {
+            Some(pair) => {
+                let then = Expr::from(pair, offset)?;
+                let other = Expr::from(pairs.next().unwrap(), offset)?;
+                Expr::Cond(Box::new(cond), Box::new(then), Box::new(other), range)
+            },
+            None => cond,
+        })
+    }
+
+    fn from_lambda(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
+        let range = Some(pair.to_range(offset));
+        let mut pairs = pair.into_inner().collect::<Vec<_>>();
+        let body = Expr::from(pairs.pop().unwrap(), offset)?;
+        let params = pairs.into_iter().map(|pair| Ok(match pair.as_rule() {
+            Rule::pattern => (Pattern::from(pair, offset)?, None),
+            Rule::param => {
+                let mut pairs = pair.into_inner();
+                let pattern = Pattern::from(pairs.next().unwrap(), offset)?;
+                (pattern, pairs.next().map(|p| Expr::from(p, offset)).transpose()?)
+            },
+            _ => unreachable!("unexpected rule: {:?}", pair.as_rule()),
+        })).collect::<Result<Vec<_>, _>>()?;
+        let rest = params.iter().rev().skip(1).find_map(|(pattern, _)| pattern.rest_range());
+        if let Some(range) = rest {
+            return Err(SyntaxError {
+                message: "rest parameter must be last".into(),
+                range,
+            });
+        }
+        Ok(Expr::Lambda(params, Box::new(body), range))
+    }
+
+    fn from_item(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
+        match pair.as_rule() {
+            Rule::spread => {
+                let range = Some(pair.to_range(offset));
+                let pair = pair.into_inner().next().unwrap();
+                Ok(Expr::Spread(Box::new(Self::from(pair, offset)?), range))
+            },
+            _ => Self::from(pair, offset),
+        }
+    }
+
+    fn from_list(pairs: Pairs<Rule>, offset: usize) -> Result<Vec<Self>, SyntaxError> {
         let mut exprs = vec![];
         for pair in pairs {
-            exprs.push(Self::from(pair, offset));
+            exprs.push(Self::from_item(pair, offset)?);
         }
-        exprs
+        Ok(exprs)
     }
 
-    fn from_entry(pair: Pair<Rule>, offset: usize) -> (Self, Option<Self>) {
+    fn from_arg(pair: Pair<Rule>, offset: usize) -> Result<(Option<String>, Self), SyntaxError> {
+        assert!(matches!(pair.as_rule(), Rule::arg));
+        let mut pairs = pair.into_inner().collect::<Vec<_>>();
+        let expr = Self::from_item(pairs.pop().unwrap(), offset)?;
+        Ok((pairs.pop().map(|pair| pair.as_str().to_string()), expr))
+    }
+
+    fn from_entry(pair: Pair<Rule>, offset: usize) -> Result<(Self, Option<Self>), SyntaxError> {
         assert!(matches!(pair.as_rule(), Rule::entry));
         let mut pairs = pair.into_inner();
         let pair = pairs.next().unwrap();
-        let key = Self::parse_literal(pair, offset);
-        let value = pairs.next().map(|p| Self::from(p, offset));
-        (key, value)
+        if let Rule::spread = pair.as_rule() {
+            return Ok((Self::from_item(pair, offset)?, None));
+        }
+        let key = Self::parse_key(pair, offset)?;
+        let value = pairs.next().map(|p| Self::from(p, offset)).transpose()?;
+        Ok((key, value))
     }
 
-    fn from_entries(pairs: Pairs<Rule>, offset: usize) -> Vec<(Self, Option<Self>)> {
+    fn from_entries(pairs: Pairs<Rule>, offset: usize) -> Result<Vec<(Self, Option<Self>)>, SyntaxError> {
         let mut entries = vec![];
         for pair in pairs {
-            entries.push(Self::from_entry(pair, offset));
+            entries.push(Self::from_entry(pair, offset)?);
         }
-        entries
+        Ok(entries)
     }
 
-    fn from_suffix(self, pair: Pair<Rule>, offset: usize) -> Self {
+    fn from_suffix(self, pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
         assert!(matches!(pair.as_rule(), Rule::suffix));
         let range = Some(pair.to_range(offset));
-        match pair.as_str().chars().nth(0) {
+        let mut text = pair.as_str();
+        let mut pairs = pair.into_inner().peekable();
+        let mut expr = self;
+        if let Some(Rule::optional) = pairs.peek().map(|pair| pair.as_rule()) {
+            let pair = pairs.next().unwrap();
+            expr = Expr::Optional(Box::new(expr), Some(pair.to_range(offset)));
+            text = &text[pair.as_str().len()..];
+        }
+        Ok(match text.chars().nth(0) {
             Some('(') => {
-                let pairs = pair.into_inner();
-                Expr::Apply(Box::new(self), Expr::from_list(pairs, offset), range)
+                let args = pairs.map(|pair| Expr::from_arg(pair, offset)).collect::<Result<_, _>>()?;
+                Expr::Apply(Box::new(expr), args, range)
             },
             Some('[') => {
-                let pair = pair.into_inner().into_iter().next().unwrap();
-                Expr::Index(Box::new(self), Box::from(Expr::from(pair, offset)), true, range)
+                let pair = pairs.next().unwrap();
+                if pair.as_rule() != Rule::slice {
+                    return Ok(Expr::Index(Box::new(expr), Box::from(Expr::from(pair, offset)?), true, range));
+                }
+                let has_start = !pair.as_str().starts_with(':');
+                let mut pairs = pair.into_inner().map(|pair| Expr::from(pair, offset).map(Box::new));
+                let start = if has_start { pairs.next().transpose()? } else { None };
+                let end = pairs.next().transpose()?;
+                Expr::Slice(Box::new(expr), start, end, range)
             },
-            Some('.') => {
-                let pair = pair.into_inner().into_iter().next().unwrap();
-                let expr = Expr::String(pair.as_str().to_string(), Some(pair.to_range(offset)));
-                Expr::Index(Box::new(self), Box::from(expr), false, range)
+            _ => {
+                let pair = pairs.next().unwrap();
+                let key = Expr::String(pair.as_str().to_string(), Some(pair.to_range(offset)));
+                Expr::Index(Box::new(expr), Box::from(key), false, range)
             },
-            _ => unreachable!(),
-        }
+        })
     }
 
-    pub fn parse_literal(pair: Pair<Rule>, offset: usize) -> Self {
-        let range = Some(pair.to_range(offset));
+    /// Parse the key of an object entry, where `[expr]` is a computed key.
+    pub fn parse_key(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
         match pair.as_rule() {
-            Rule::ident => Expr::Ident(pair.as_str().to_string(), range),
-            Rule::number => Expr::Number(pair.as_str().parse().unwrap(), range),
+            Rule::expr => {
+                let range = Some(pair.to_range(offset));
+                Ok(Expr::Computed(Box::new(Expr::from(pair, offset)?), range))
+            },
+            _ => Expr::parse_literal(pair, offset),
+        }
+    }
+
+    pub fn parse_literal(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
+        let range = pair.to_range(offset);
+        Ok(match pair.as_rule() {
+            Rule::ident => Expr::Ident(pair.as_str().to_string(), Some(range)),
+            Rule::number => match pair.as_str().replace('_', "").parse::<f64>().unwrap() {
+                n if n.is_finite() => Expr::Number(n, Some(range)),
+                _ => return Err(SyntaxError {
+                    message: "number literal out of range".into(),
+                    range,
+                }),
+            },
+            Rule::integer => {
+                let str = pair.as_str().replace('_', "");
+                let (digits, radix) = match str.get(..2) {
+                    Some("0x") => (&str[2..], 16),
+                    Some("0o") => (&str[2..], 8),
+                    Some("0b") => (&str[2..], 2),
+                    _ => (str.as_str(), 10),
+                };
+                match i64::from_str_radix(digits, radix) {
+                    Ok(n) => Expr::Integer(n, Some(range)),
+                    Err(_) => return Err(SyntaxError {
+                        message: "integer literal out of range".into(),
+                        range,
+                    }),
+                }
+            },
             Rule::string => {
                 let str = pair.as_str();
-                let mut str = &str[1..str.len() - 1];
-                let mut inner = String::new();
-                while let Some(i) = str.find('\\') {
-                    inner += &str[..i];
-                    inner.push(match str.chars().nth(i + 1).unwrap() {
-                        'n' => '\n',
-                        'r' => '\r',
-                        't' => '\t',
-                        c => c,
-                    });
-                    str = &str[i + 2..];
-                }
-                inner += str;
-                Expr::String(inner, range)
+                Expr::String(unescape(&str[1..str.len() - 1], range)?, Some(range))
+            },
+            Rule::template => {
+                let parts = pair.into_inner().map(|pair| match pair.as_rule() {
+                    Rule::template_text => {
+                        let range = pair.to_range(offset);
+                        Ok(Expr::String(unescape(pair.as_str(), range)?, Some(range)))
+                    },
+                    _ => Expr::from(pair.into_inner().next().unwrap(), offset),
+                }).collect::<Result<_, _>>()?;
+                Expr::Template(parts, Some(range))
             },
             Rule::array => {
                 let pairs = pair.into_inner();
-                Expr::Array(Expr::from_list(pairs, offset), range)
+                Expr::Array(Expr::from_list(pairs, offset)?, Some(range))
             },
             Rule::object => {
                 let pairs = pair.into_inner();
-                Expr::Object(Expr::from_entries(pairs, offset), range)
+                Expr::Object(Expr::from_entries(pairs, offset)?, Some(range))
             },
-            Rule::expr => Expr::from(pair, offset),
+            Rule::expr => Expr::from(pair, offset)?,
             _ => unreachable!("unexpected rule: {:?}", pair.as_rule()),
-        }
+        })
     }
 
-    fn from_unary(pair: Pair<Rule>, offset: usize) -> Self {
+    fn from_unary(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
         let pairs = pair.into_inner().collect::<Vec<_>>();
         let index = pairs.iter().position(|pair| pair.as_rule() == Rule::literal).unwrap();
         let pair = pairs[index].clone().into_inner().next().unwrap();
-        let mut expr = Expr::parse_literal(pair, offset);
+        let mut expr = Expr::parse_literal(pair, offset)?;
         for i in index + 1..pairs.len() {
-            expr = Expr::from_suffix(expr, pairs[i].clone(), offset);
+            expr = Expr::from_suffix(expr, pairs[i].clone(), offset)?;
         }
         for i in (0..index).rev() {
             let op = UnaryOp::from(pairs[i].as_str());
             expr = Expr::Unary(op, Box::new(expr), Some(pairs[i].to_range(offset)));
         }
-        expr
+        Ok(expr)
     }
 
-    fn from_pow(pair: Pair<Rule>, offset: usize) -> Self {
+    fn from_pow(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
         let mut pairs = pair.into_inner().rev();
-        let mut expr = Expr::from_unary(pairs.next().unwrap(), offset);
+        let mut expr = Expr::from_unary(pairs.next().unwrap(), offset)?;
         while pairs.len() > 0 {
             let pair = pairs.next().unwrap();
             let op = BinaryOp::from(pair.as_str());
-            let lhs = Expr::from_unary(pairs.next().unwrap(), offset);
+            let lhs = Expr::from_unary(pairs.next().unwrap(), offset)?;
             expr = Expr::Binary(Box::new(lhs), op, Box::new(expr), Some(pair.to_range(offset)));
         }
-        expr
+        Ok(expr)
     }
 
     left_assoc!(from_mul, from_pow);
@@ -174,4 +289,211 @@ impl Expr {
     left_assoc!(from_bitor, from_bitxor);
     left_assoc!(from_and, from_bitor);
     left_assoc!(from_or, from_and);
+    left_assoc!(from_nullish, from_or);
+}
+
+/// Resolve escape sequences in the content of a string or template literal.
+/// 
+/// Supports `\n`, `\r`, `\t`, `\b`, `\f`, `\v`, `\0`, quotes, backslashes, `\xHH`,
+/// `\uHHHH`, `\u{H...}` and line continuations. Octal escapes are not supported.
+fn unescape(str: &str, range: (usize, usize)) -> Result<String, SyntaxError> {
+    let error = |message: String| SyntaxError { message, range };
+    let mut output = String::new();
+    let mut chars = str.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        let Some(c) = chars.next() else {
+            return Err(error("unterminated escape sequence".into()));
+        };
+        match c {
+            'n' => output.push('\n'),
+            'r' => output.push('\r'),
+            't' => output.push('\t'),
+            'b' => output.push('\u{8}'),
+            'f' => output.push('\u{c}'),
+            'v' => output.push('\u{b}'),
+            '0' if chars.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                return Err(error("octal escape sequences are not supported".into()));
+            },
+            '0' => output.push('\0'),
+            '\\' | '\'' | '"' | '`' | '$' => output.push(c),
+            '\n' => {},
+            '\r' => {
+                chars.next_if_eq(&'\n');
+            },
+            'x' => {
+                let hex = chars.by_ref().take(2).collect::<String>();
+                match parse_hex(&hex) {
+                    Some(n) if hex.len() == 2 => output.push(char::from(n as u8)),
+                    _ => return Err(error(format!("invalid escape sequence '\\x{}'", hex))),
+                }
+            },
+            'u' => {
+                let hex = match chars.next_if_eq(&'{') {
+                    Some(_) => {
+                        let mut hex = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('}') if !hex.is_empty() => break Some(hex),
+                                Some(c) if c != '}' && hex.len() < 6 => hex.push(c),
+                                _ => break None,
+                            }
+                        }
+                    },
+                    None => Some(chars.by_ref().take(4).collect::<String>()).filter(|hex| hex.len() == 4),
+                };
+                match hex.as_deref().and_then(parse_hex).and_then(char::from_u32) {
+                    Some(c) => output.push(c),
+                    None => return Err(error("invalid unicode escape sequence".into())),
+                }
+            },
+            c => return Err(error(format!("invalid escape sequence '\\{}'", c))),
+        }
+    }
+    Ok(output)
+}
+
+fn parse_hex(hex: &str) -> Option<u32> {
+    match hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        true => u32::from_str_radix(hex, 16).ok(),
+        false => None,
+    }
+}
+
+impl Expr {
+    pub(super) fn fmt_key(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Ident(ident, _) => write!(f, "{}", ident),
+            Self::Computed(expr, _) => write!(f, "[{}]", expr),
+            expr => write!(f, "{}", expr),
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Binary(..) | Self::Lambda(..) | Self::Cond(..) => write!(f, "({})", self),
+            expr => write!(f, "{}", expr),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Integer(n, _) => write!(f, "{}", n),
+            Self::Number(n, _) => write!(f, "{:?}", n),
+            Self::String(s, _) => write!(f, "{:?}", s),
+            Self::Ident(ident, _) => write!(f, "{}", ident),
+            Self::Array(vec, _) => {
+                write!(f, "[")?;
+                for (i, expr) in vec.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", expr)?;
+                }
+                write!(f, "]")
+            },
+            Self::Object(entries, _) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match value {
+                        Some(value) => {
+                            key.fmt_key(f)?;
+                            write!(f, ": {}", value)?;
+                        },
+                        None => write!(f, "{}", key)?,
+                    }
+                }
+                write!(f, "}}")
+            },
+            Self::Apply(func, args, _) => {
+                func.fmt_operand(f)?;
+                write!(f, "(")?;
+                for (i, (name, expr)) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    if let Some(name) = name {
+                        write!(f, "{} = ", name)?;
+                    }
+                    write!(f, "{}", expr)?;
+                }
+                write!(f, ")")
+            },
+            Self::Unary(op, expr, _) => {
+                write!(f, "{}", op)?;
+                expr.fmt_operand(f)
+            },
+            Self::Binary(lhs, op, rhs, _) => {
+                lhs.fmt_operand(f)?;
+                write!(f, " {} ", op)?;
+                rhs.fmt_operand(f)
+            },
+            Self::Index(lhs, rhs, is_expr, _) => {
+                lhs.fmt_operand(f)?;
+                match (is_expr, rhs.as_ref()) {
+                    (false, Self::String(key, _)) if matches!(lhs.as_ref(), Self::Optional(..)) => write!(f, "{}", key),
+                    (false, Self::String(key, _)) => write!(f, ".{}", key),
+                    _ => write!(f, "[{}]", rhs),
+                }
+            },
+            Self::Slice(lhs, start, end, _) => {
+                lhs.fmt_operand(f)?;
+                write!(f, "[")?;
+                if let Some(start) = start {
+                    write!(f, "{}", start)?;
+                }
+                write!(f, ":")?;
+                if let Some(end) = end {
+                    write!(f, "{}", end)?;
+                }
+                write!(f, "]")
+            },
+            Self::Lambda(params, body, _) => {
+                write!(f, "(")?;
+                for (i, (pattern, default)) in params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", pattern)?;
+                    if let Some(default) = default {
+                        write!(f, " = {}", default)?;
+                    }
+                }
+                write!(f, ") => {}", body)
+            },
+            Self::Spread(expr, _) => write!(f, "...{}", expr),
+            Self::Optional(expr, _) => {
+                expr.fmt_operand(f)?;
+                write!(f, "?.")
+            },
+            Self::Cond(cond, then, other, _) => {
+                cond.fmt_operand(f)?;
+                write!(f, " ? ")?;
+                then.fmt_operand(f)?;
+                write!(f, " : ")?;
+                other.fmt_operand(f)
+            },
+            Self::Computed(expr, _) => write!(f, "{}", expr),
+            Self::Template(parts, _) => {
+                write!(f, "`")?;
+                for part in parts {
+                    match part {
+                        Self::String(s, _) => {
+                            write!(f, "{}", s.replace('\\', "\\\\").replace('`', "\\`").replace("${", "\\${"))?;
+                        },
+                        expr => write!(f, "${{{}}}", expr)?,
+                    }
+                }
+                write!(f, "`")
+            },
+        }
+    }
 }
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use serde::de::Error;
+
+use super::{NumberFormat, RuntimeError, Value};
+
+const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+/// A translated message, which may depend on a count.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Text(String),
+    Plural {
+        /// Variants keyed by plural category (`zero`, `one`, `two`, `few`, `many`)
+        /// or by an exact count such as `=0`.
+        variants: BTreeMap<String, String>,
+        /// The variant used when no other variant matches.
+        other: String,
+    },
+}
+
+fn is_plural_key(key: &str) -> bool {
+    PLURAL_CATEGORIES.contains(&key) || key.strip_prefix('=').is_some_and(|n| n.parse::<f64>().is_ok())
+}
+
+/// The messages of a single locale.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog(HashMap<String, Message>);
+
+impl Catalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, message: Message) {
+        self.0.insert(key.into(), message);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Message> {
+        self.0.get(key)
+    }
+
+    /// Load a catalog from a JSON object.
+    ///
+    /// Nested objects are flattened into dotted keys, except for objects
+    /// with an `other` entry whose keys are all plural categories or exact counts,
+    /// which are plural messages.
+    pub fn from_json(input: &str) -> Result<Self, serde_json::Error> {
+        let mut catalog = Self::new();
+        catalog.extend_json("", serde_json::from_str(input)?)?;
+        Ok(catalog)
+    }
+
+    fn extend_json(&mut self, prefix: &str, value: serde_json::Value) -> Result<(), serde_json::Error> {
+        let serde_json::Value::Object(map) = value else {
+            return Err(serde_json::Error::custom(format!("expect object at '{}'", prefix)));
+        };
+        for (key, value) in map {
+            let key = match prefix {
+                "" => key,
+                _ => format!("{}.{}", prefix, key),
+            };
+            match value {
+                serde_json::Value::String(text) => self.insert(key, Message::Text(text)),
+                serde_json::Value::Object(map) if map.contains_key("other") && map.keys().all(|k| is_plural_key(k)) => {
+                    let mut variants = BTreeMap::new();
+                    for (category, value) in map {
+                        let serde_json::Value::String(text) = value else {
+                            return Err(serde_json::Error::custom(format!("expect string at '{}.{}'", key, category)));
+                        };
+                        variants.insert(category, text);
+                    }
+                    let other = variants.remove("other").unwrap();
+                    self.insert(key, Message::Plural { variants, other });
+                },
+                value => self.extend_json(&key, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What to render when a key has no translation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingKey {
+    /// Render the key itself.
+    #[default]
+    Key,
+    /// Render nothing.
+    Empty,
+    /// Raise a runtime error.
+    Error,
+}
+
+/// Translation settings used by the `t` function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct I18n {
+    /// The locale to translate into, such as `en` or `pt-BR`.
+    pub locale: String,
+    /// The locale to use when a key is missing from the current locale.
+    pub fallback: Option<String>,
+    /// Catalogs keyed by locale.
+    pub catalogs: HashMap<String, Catalog>,
+    pub missing: MissingKey,
+}
+
+impl Default for I18n {
+    fn default() -> Self {
+        Self {
+            locale: "en".into(),
+            fallback: None,
+            catalogs: HashMap::new(),
+            missing: MissingKey::default(),
+        }
+    }
+}
+
+impl I18n {
+    /// Find a message for the current locale, then for its language, then for the fallback locale.
+    /// Returns the locale of the message as well, which decides its plural rules.
+    pub fn lookup(&self, key: &str) -> Option<(&str, &Message)> {
+        let language = self.locale.split(['-', '_']).next().unwrap();
+        [Some(self.locale.as_str()), Some(language), self.fallback.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|locale| Some((locale, self.catalogs.get(locale)?.get(key)?)))
+    }
+
+    /// Translate a key, replacing `{name}` placeholders with parameters.
+    /// The `count` parameter selects the variant of a plural message.
+    pub fn translate(&self, key: &str, params: &BTreeMap<String, Rc<Value>>, number: &NumberFormat) -> Result<String, RuntimeError> {
+        let Some((locale, message)) = self.lookup(key) else {
+            return match self.missing {
+                MissingKey::Key => Ok(key.into()),
+                MissingKey::Empty => Ok(String::new()),
+                MissingKey::Error => Err(RuntimeError {
+                    message: format!("missing translation for '{}' in locale '{}'", key, self.locale),
+                }),
+            };
+        };
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Plural { variants, other } => {
+                let count = match params.get("count") {
+                    Some(count) => count.as_number()?,
+                    None => return Err(RuntimeError {
+                        message: format!("missing count for plural message '{}'", key),
+                    }),
+                };
+                variants.get(&format!("={}", count))
+                    .or_else(|| variants.get(plural_category(locale, count)))
+                    .unwrap_or(other)
+            },
+        };
+        Ok(interpolate(text, params, number))
+    }
+}
+
+fn interpolate(text: &str, params: &BTreeMap<String, Rc<Value>>, number: &NumberFormat) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        output += &rest[..start];
+        let name = &rest[start + 1..start + len];
+        match params.get(name.trim()) {
+            Some(value) => output += &value.format(number),
+            None => output += &rest[start..=start + len],
+        }
+        rest = &rest[start + len + 1..];
+    }
+    output + rest
+}
+
+/// The CLDR plural category of a count for the cardinal rules of common languages.
+/// Languages without specific rules use the English rules.
+pub fn plural_category(locale: &str, n: f64) -> &'static str {
+    let language = locale.split(['-', '_']).next().unwrap().to_ascii_lowercase();
+    let integral = n.fract() == 0.;
+    let i = n.abs().trunc() as u64;
+    match language.as_str() {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => "other",
+        "fr" | "pt" if i <= 1 => "one",
+        "fr" | "pt" => "other",
+        "ru" | "uk" | "be" | "pl" | "cs" | "sk" if !integral => match language.as_str() {
+            "cs" | "sk" => "many",
+            _ => "other",
+        },
+        "ru" | "uk" | "be" => match (i % 10, i % 100) {
+            (1, m) if m != 11 => "one",
+            (2..=4, m) if !(12..=14).contains(&m) => "few",
+            _ => "many",
+        },
+        "pl" => match (i, i % 10, i % 100) {
+            (1, _, _) => "one",
+            (_, 2..=4, m) if !(12..=14).contains(&m) => "few",
+            _ => "many",
+        },
+        "cs" | "sk" => match i {
+            1 => "one",
+            2..=4 => "few",
+            _ => "other",
+        },
+        "ar" if integral => match (i, i % 100) {
+            (0, _) => "zero",
+            (1, _) => "one",
+            (2, _) => "two",
+            (_, 3..=10) => "few",
+            (_, 11..=99) => "many",
+            _ => "other",
+        },
+        _ if n == 1. => "one",
+        _ => "other",
+    }
+}
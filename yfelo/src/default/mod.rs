@@ -1,16 +1,23 @@
 use std::fmt::Debug;
 
-use super::{SyntaxError, factory};
+use super::{ParseError, factory};
 
+mod config;
 mod context;
+mod date;
 mod expr;
+mod i18n;
 mod operator;
 mod parser;
 mod pattern;
+pub mod stdlib;
 mod value;
 
+pub use config::*;
 pub use context::*;
+pub use date::*;
 pub use expr::*;
+pub use i18n::*;
 pub use operator::*;
 pub use pattern::*;
 pub use value::*;
@@ -18,11 +25,11 @@ pub use value::*;
 pub struct Language;
 
 impl factory::Language<Expr, Pattern> for Language {
-    fn parse_expr(input: &str, offset: usize) -> Result<(Expr, usize), SyntaxError> {
+    fn parse_expr(input: &str, offset: usize) -> Result<(Expr, usize), ParseError> {
         Expr::parse(input, offset)
     }
 
-    fn parse_pattern(input: &str, offset: usize) -> Result<(Pattern, usize), SyntaxError> {
+    fn parse_pattern(input: &str, offset: usize) -> Result<(Pattern, usize), ParseError> {
         Pattern::parse(input, offset)
     }
 }
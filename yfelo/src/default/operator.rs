@@ -1,4 +1,22 @@
-use super::{RuntimeError, Value};
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{NumberFormat, RuntimeError, Value};
+
+fn overflow() -> RuntimeError {
+    RuntimeError {
+        message: "integer overflow".into(),
+    }
+}
+
+/// Apply an arithmetic operation, which is performed on integers
+/// if both operands are integers, and on floats otherwise.
+fn arith(lhs: &Value, rhs: &Value, int: fn(i64, i64) -> Option<i64>, float: fn(f64, f64) -> f64) -> Result<Value, RuntimeError> {
+    match (lhs.deref(), rhs.deref()) {
+        (Value::Integer(a), Value::Integer(b)) => int(*a, *b).map(Value::Integer).ok_or_else(overflow),
+        _ => Ok(Value::Number(float(lhs.as_number()?, rhs.as_number()?))),
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UnaryOp {
@@ -17,11 +35,25 @@ impl UnaryOp {
         }
     }
 
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Not => "!",
+            Self::Pos => "+",
+            Self::Neg => "-",
+        }
+    }
+
     pub fn eval(&self, value: Value) -> Result<Value, RuntimeError> {
         Ok(match self {
             Self::Not => Value::Bool(!value.as_bool()?),
-            Self::Pos => Value::Number(value.as_number()?),
-            Self::Neg => Value::Number(-value.as_number()?),
+            Self::Pos => match value.deref() {
+                Value::Integer(n) => Value::Integer(*n),
+                _ => Value::Number(value.as_number()?),
+            },
+            Self::Neg => match value.deref() {
+                Value::Integer(n) => Value::Integer(n.checked_neg().ok_or_else(overflow)?),
+                _ => Value::Number(-value.as_number()?),
+            },
         })
     }
 }
@@ -32,13 +64,14 @@ pub enum BinaryOp {
     Mul, Div, Mod,
     Add, Sub,
     Shl, Shr,
-    Lt, Le, Gt, Ge,
+    Lt, Le, Gt, Ge, In, NotIn,
     Eq, Ne,
     BitAnd,
     BitXor,
     BitOr,
     And,
     Or,
+    Nullish,
 }
 
 impl BinaryOp {
@@ -56,6 +89,8 @@ impl BinaryOp {
             "<=" => Self::Le,
             ">" => Self::Gt,
             ">=" => Self::Ge,
+            "in" => Self::In,
+            _ if input.split_whitespace().eq(["not", "in"]) => Self::NotIn,
             "==" => Self::Eq,
             "!=" => Self::Ne,
             "&" => Self::BitAnd,
@@ -63,37 +98,104 @@ impl BinaryOp {
             "|" => Self::BitOr,
             "&&" => Self::And,
             "||" => Self::Or,
+            "??" => Self::Nullish,
             _ => unreachable!(),
         }
     }
 
-    pub fn eval(&self, lhs: Value, rhs: Value) -> Result<Value, RuntimeError> {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pow => "**",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::In => "in",
+            Self::NotIn => "not in",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::BitAnd => "&",
+            Self::BitXor => "^",
+            Self::BitOr => "|",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Nullish => "??",
+        }
+    }
+
+    /// Apply the operator, formatting numbers concatenated to strings with `number`.
+    /// Short-circuit operators are evaluated by the context instead.
+    pub(crate) fn eval(&self, lhs: Value, rhs: Value, number: &NumberFormat) -> Result<Value, RuntimeError> {
         Ok(match self {
-            Self::Pow => Value::Number(lhs.as_number()?.powf(rhs.as_number()?)),
-            Self::Mul => Value::Number(lhs.as_number()? * rhs.as_number()?),
+            Self::Pow => match (lhs.deref(), rhs.deref()) {
+                (Value::Integer(a), Value::Integer(b)) if *b >= 0 => {
+                    let b = u32::try_from(*b).map_err(|_| overflow())?;
+                    Value::Integer(a.checked_pow(b).ok_or_else(overflow)?)
+                },
+                _ => Value::Number(lhs.as_number()?.powf(rhs.as_number()?)),
+            },
+            Self::Mul => arith(&lhs, &rhs, i64::checked_mul, |a, b| a * b)?,
+            Self::Div | Self::Mod if matches!((lhs.deref(), rhs.deref()), (Value::Integer(_), Value::Integer(0))) => {
+                return Err(RuntimeError {
+                    message: "division by zero".into(),
+                });
+            },
+            // division always produces a float, and a float divisor of zero gives `inf` or `nan`
             Self::Div => Value::Number(lhs.as_number()? / rhs.as_number()?),
-            Self::Mod => Value::Number(lhs.as_number()? % rhs.as_number()?),
+            Self::Mod => arith(&lhs, &rhs, i64::checked_rem, |a, b| a % b)?,
             Self::Add => match lhs.deref() {
-                Value::String(lhs) => Value::String(lhs.clone() + &rhs.as_string()?),
-                Value::Number(lhs) => Value::Number(*lhs + rhs.as_number()?),
+                Value::String(lhs) => Value::String(lhs.clone() + &rhs.as_string_with(number)?),
+                Value::Integer(_) | Value::Number(_) => arith(&lhs, &rhs, i64::checked_add, |a, b| a + b)?,
                 _ => return Err(RuntimeError {
                     message: format!("cannot add {} and {}", lhs.type_name(), rhs.type_name()),
                 }),
             },
-            Self::Sub => Value::Number(lhs.as_number()? - rhs.as_number()?),
-            Self::Shl => Value::Number(((lhs.as_number()? as i64) << (rhs.as_number()? as i64)) as f64),
-            Self::Shr => Value::Number(((lhs.as_number()? as i64) >> (rhs.as_number()? as i64)) as f64),
-            Self::Lt => Value::Bool(lhs.as_number()? < rhs.as_number()?),
-            Self::Le => Value::Bool(lhs.as_number()? <= rhs.as_number()?),
-            Self::Gt => Value::Bool(lhs.as_number()? > rhs.as_number()?),
-            Self::Ge => Value::Bool(lhs.as_number()? >= rhs.as_number()?),
+            Self::Sub => arith(&lhs, &rhs, i64::checked_sub, |a, b| a - b)?,
+            Self::Shl => {
+                let (a, b) = (lhs.as_integer()?, u32::try_from(rhs.as_integer()?).map_err(|_| overflow())?);
+                // bits shifted out of the value, including the sign bit, are an overflow
+                match a.checked_shl(b) {
+                    Some(value) if value >> b == a => Value::Integer(value),
+                    _ => return Err(overflow()),
+                }
+            },
+            Self::Shr => {
+                let b = u32::try_from(rhs.as_integer()?).map_err(|_| overflow())?;
+                Value::Integer(lhs.as_integer()?.checked_shr(b).ok_or_else(overflow)?)
+            },
+            Self::Lt => Value::Bool(matches!(lhs.compare(&rhs)?, Some(Ordering::Less))),
+            Self::Le => Value::Bool(matches!(lhs.compare(&rhs)?, Some(Ordering::Less | Ordering::Equal))),
+            Self::Gt => Value::Bool(matches!(lhs.compare(&rhs)?, Some(Ordering::Greater))),
+            Self::Ge => Value::Bool(matches!(lhs.compare(&rhs)?, Some(Ordering::Greater | Ordering::Equal))),
+            Self::In => Value::Bool(rhs.contains(&lhs)?),
+            Self::NotIn => Value::Bool(!rhs.contains(&lhs)?),
             Self::Eq => Value::Bool(lhs == rhs),
             Self::Ne => Value::Bool(lhs != rhs),
-            Self::BitAnd => Value::Number((lhs.as_number()? as i64 & rhs.as_number()? as i64) as f64),
-            Self::BitXor => Value::Number((lhs.as_number()? as i64 ^ rhs.as_number()? as i64) as f64),
-            Self::BitOr => Value::Number((lhs.as_number()? as i64 | rhs.as_number()? as i64) as f64),
-            Self::And => Value::Bool(lhs.as_bool()? && rhs.as_bool()?),
-            Self::Or => Value::Bool(lhs.as_bool()? || rhs.as_bool()?),
+            Self::BitAnd => Value::Integer(lhs.as_integer()? & rhs.as_integer()?),
+            Self::BitXor => Value::Integer(lhs.as_integer()? ^ rhs.as_integer()?),
+            Self::BitOr => Value::Integer(lhs.as_integer()? | rhs.as_integer()?),
+            Self::And | Self::Or | Self::Nullish => return Err(RuntimeError {
+                message: format!("operator '{}' cannot be applied to evaluated operands", self),
+            }),
         })
     }
 }
+
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt;
 
 use pest::{iterators::{Pair, Pairs}, Parser};
-use yfelo_core::{factory, SyntaxError};
+use yfelo_core::{factory, ParseError, SyntaxError};
 
 use super::parser::{DefaultParser, Rule, ToRange};
 use super::Expr;
@@ -11,6 +11,8 @@ pub enum Pattern {
     Ident(String, Option<(usize, usize)>),
     Array(Vec<Pattern>, Option<(usize, usize)>),
     Object(Vec<(Expr, Option<Pattern>)>, Option<(usize, usize)>),
+    Rest(String, Option<(usize, usize)>),
+    Default(Box<Pattern>, Box<Expr>, Option<(usize, usize)>),
 }
 
 impl factory::Pattern for Pattern {
@@ -20,62 +22,154 @@ impl factory::Pattern for Pattern {
             _ => None,
         }
     }
+
+    fn is_rest(&self) -> bool {
+        matches!(self, Pattern::Rest(..))
+    }
 }
 
 impl Pattern {
-    pub fn parse(input: &str, offset: usize) -> Result<(Pattern, usize), SyntaxError> {
+    /// Whether the pattern binds a variable of this name.
+    pub fn binds(&self, name: &str) -> bool {
+        match self {
+            Pattern::Ident(ident, _) | Pattern::Rest(ident, _) => ident == name,
+            Pattern::Array(pats, _) => pats.iter().any(|pattern| pattern.binds(name)),
+            Pattern::Object(entries, _) => entries.iter().any(|(key, pattern)| match pattern {
+                Some(pattern) => pattern.binds(name),
+                None => matches!(key, Expr::Ident(ident, _) if ident == name),
+            }),
+            Pattern::Default(pattern, _, _) => pattern.binds(name),
+        }
+    }
+
+    /// The variable bound by an identifier pattern, with or without a default.
+    fn shorthand(&self) -> Option<&str> {
+        match self {
+            Pattern::Ident(ident, _) => Some(ident),
+            Pattern::Default(pattern, _, _) => match pattern.as_ref() {
+                Pattern::Ident(ident, _) => Some(ident),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The span of a rest pattern, used to report misplaced ones.
+    pub(super) fn rest_range(&self) -> Option<(usize, usize)> {
+        match self {
+            Pattern::Rest(_, range) => *range,
+            _ => None,
+        }
+    }
+
+    pub fn parse(input: &str, offset: usize) -> Result<(Pattern, usize), ParseError> {
         match DefaultParser::parse(Rule::pattern, input) {
             Ok(pairs) => {
                 let len = pairs.as_str().len();
-                Ok((Pattern::from(pairs.into_iter().next().unwrap(), offset), len))
+                Ok((Pattern::from(pairs.into_iter().next().unwrap(), offset)?, len))
             },
-            Err(e) => return Err(SyntaxError {
+            Err(e) => return Err(ParseError::Incomplete(SyntaxError {
                 message: e.to_string(),
                 range: e.location.to_range(offset),
-            }),
+            })),
         }
     }
 
-    fn from(pair: Pair<Rule>, offset: usize) -> Self {
+    pub(super) fn from(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
         let pair = pair.into_inner().next().unwrap();
         let range = Some(pair.to_range(offset));
-        match pair.as_rule() {
+        Ok(match pair.as_rule() {
             Rule::ident => Pattern::Ident(pair.as_str().to_string(), range),
+            Rule::pat_rest => {
+                let pair = pair.into_inner().next().unwrap();
+                Pattern::Rest(pair.as_str().to_string(), range)
+            },
             Rule::pat_array => {
                 let pairs = pair.into_inner();
-                Self::Array(Self::from_list(pairs, offset), range)
+                Self::Array(Self::from_list(pairs, offset)?, range)
             },
             Rule::pat_object => {
                 let pairs = pair.into_inner();
-                Self::Object(Self::from_entries(pairs, offset), range)
+                Self::Object(Self::from_entries(pairs, offset)?, range)
             },
             _ => unreachable!(),
-        }
+        })
+    }
+
+    fn from_item(pair: Pair<Rule>, offset: usize) -> Result<Self, SyntaxError> {
+        assert!(matches!(pair.as_rule(), Rule::pat_item));
+        let range = Some(pair.to_range(offset));
+        let mut pairs = pair.into_inner();
+        let pattern = Self::from(pairs.next().unwrap(), offset)?;
+        Ok(match pairs.next() {
+            Some(pair) if pattern.rest_range().is_some() => return Err(SyntaxError {
+                message: "rest element cannot have a default value".into(),
+                range: pair.to_range(offset),
+            }),
+            Some(pair) => Self::Default(Box::new(pattern), Box::new(Expr::parse_literal(pair, offset)?), range),
+            None => pattern,
+        })
     }
 
-    fn from_list(pairs: Pairs<Rule>, offset: usize) -> Vec<Self> {
-        let mut exprs = vec![];
+    fn from_list(pairs: Pairs<Rule>, offset: usize) -> Result<Vec<Self>, SyntaxError> {
+        let mut exprs: Vec<Self> = vec![];
         for pair in pairs {
-            exprs.push(Self::from(pair, offset));
+            if let Some(range) = exprs.last().and_then(Self::rest_range) {
+                return Err(SyntaxError {
+                    message: "rest element must be last".into(),
+                    range,
+                });
+            }
+            exprs.push(Self::from_item(pair, offset)?);
         }
-        exprs
+        Ok(exprs)
     }
 
-    fn from_entry(pair: Pair<Rule>, offset: usize) -> (Expr, Option<Self>) {
-        assert!(matches!(pair.as_rule(), Rule::entry));
+    fn from_entry(pair: Pair<Rule>, offset: usize) -> Result<(Expr, Option<Self>), SyntaxError> {
+        assert!(matches!(pair.as_rule(), Rule::pat_entry));
+        let range = Some(pair.to_range(offset));
         let mut pairs = pair.into_inner();
         let pair = pairs.next().unwrap();
-        let key = Expr::parse_literal(pair, offset);
-        let value = pairs.next().map(|p| Self::from(p, offset));
-        (key, value)
+        let key_range = Some(pair.to_range(offset));
+        Ok(match (pair.as_rule(), pairs.next()) {
+            // the key of a rest entry is never used
+            (Rule::pat_rest, _) => {
+                let pair = pair.into_inner().next().unwrap();
+                let ident = pair.as_str().to_string();
+                (Expr::Ident(ident.clone(), key_range), Some(Self::Rest(ident, key_range)))
+            },
+            (_, Some(value)) if value.as_rule() == Rule::pat_item => {
+                let pattern = Self::from_item(value, offset)?;
+                if let Self::Rest(ident, range) = pattern {
+                    return Err(SyntaxError {
+                        message: format!("unexpected rest pattern '...{}'", ident),
+                        range: range.unwrap(),
+                    });
+                }
+                (Expr::parse_key(pair, offset)?, Some(pattern))
+            },
+            (_, Some(value)) => {
+                let ident = pair.as_str().to_string();
+                let pattern = Self::Ident(ident.clone(), key_range);
+                let default = Expr::parse_literal(value, offset)?;
+                (Expr::Ident(ident, key_range), Some(Self::Default(Box::new(pattern), Box::new(default), range)))
+            },
+            (_, None) => (Expr::parse_literal(pair, offset)?, None),
+        })
     }
 
-    fn from_entries(pairs: Pairs<Rule>, offset: usize) -> Vec<(Expr, Option<Self>)> {
-        let mut entries = vec![];
+    fn from_entries(pairs: Pairs<Rule>, offset: usize) -> Result<Vec<(Expr, Option<Self>)>, SyntaxError> {
+        let mut entries: Vec<(Expr, Option<Self>)> = vec![];
         for pair in pairs {
-            entries.push(Self::from_entry(pair, offset));
+            if let Some(range) = entries.last().and_then(|(_, pattern)| pattern.as_ref()?.rest_range()) {
+                return Err(SyntaxError {
+                    message: "rest element must be last".into(),
+                    range,
+                });
+            }
+            entries.push(Self::from_entry(pair, offset)?);
         }
-        entries
+        Ok(entries)
     }
 }
 
@@ -89,6 +183,7 @@ impl fmt::Display for Pattern {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Ident(ident, _) => write!(f, "{}", ident),
+            Self::Rest(ident, _) => write!(f, "...{}", ident),
             Self::Array(vec, _) => {
                 write!(f, "[")?;
                 for (i, pattern) in vec.iter().enumerate() {
@@ -105,13 +200,22 @@ impl fmt::Display for Pattern {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "{:?}", key)?; // fixme
-                    if let Some(value) = value {
-                        write!(f, ": {}", value)?;
+                    match value {
+                        Some(value @ Self::Rest(..)) => write!(f, "{}", value)?,
+                        // shorthands, with or without a default
+                        Some(value) if matches!(key, Expr::Ident(key, _) if Some(key.as_str()) == value.shorthand()) => {
+                            write!(f, "{}", value)?
+                        },
+                        Some(value) => {
+                            key.fmt_key(f)?;
+                            write!(f, ": {}", value)?;
+                        },
+                        None => write!(f, "{}", key)?,
                     }
                 }
                 write!(f, "}}")
             },
+            Self::Default(pattern, expr, _) => write!(f, "{} = {}", pattern, expr),
         }
     }
 }
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use super::{key_of, to_usize, unpack_method, Context, Native, RuntimeError, Value};
+use crate::default::BinaryOp;
+
+pub fn method(name: &str) -> Option<Native> {
+    Some(match name {
+        "len" => len,
+        "join" => join,
+        "reverse" => reverse,
+        "index_of" => index_of,
+        "map" => map,
+        "filter" => filter,
+        "find" => find,
+        "sort" => sort,
+        "sort_by" => sort_by,
+        "group_by" => group_by,
+        "unique" => unique,
+        "sum" => sum,
+        "avg" => avg,
+        "count" => count,
+        "flatten" => flatten,
+        "zip" => zip,
+        "chunk" => chunk,
+        _ => return None,
+    })
+}
+
+fn len(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::from(this.into_array()?.len()))
+}
+
+/// `xs.join(sep?)`, joining the formatted items with `,` by default.
+fn join(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, sep] = unpack_method(args, 1)?;
+    let sep = match sep.deref() {
+        Value::Null => ",".to_string(),
+        _ => sep.as_string()?,
+    };
+    let items = this.into_array()?.iter()
+        .map(|value| value.format(&ctx.config().number))
+        .collect::<Vec<_>>();
+    Ok(Value::String(items.join(&sep)))
+}
+
+fn reverse(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    let mut vec = this.into_array()?;
+    vec.reverse();
+    Ok(Value::Array(vec))
+}
+
+/// `xs.index_of(item)`, returning the index of the first equal item or `-1`.
+fn index_of(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, item] = unpack_method(args, 2)?;
+    Ok(match this.into_array()?.iter().position(|value| value.as_ref() == &item) {
+        Some(i) => Value::from(i),
+        None => Value::Integer(-1),
+    })
+}
+
+fn map(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, f] = unpack_method(args, 2)?;
+    let mut output = vec![];
+    for value in this.into_array()? {
+        output.push(ctx.call(&f, vec![Value::from_rc(value)])?);
+    }
+    Ok(Value::from(output))
+}
+
+fn filter(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, f] = unpack_method(args, 2)?;
+    let mut output = vec![];
+    for value in this.into_array()? {
+        if ctx.call(&f, vec![Value::from_rc(value.clone())])?.as_bool()? {
+            output.push(value);
+        }
+    }
+    Ok(Value::Array(output))
+}
+
+/// `xs.find(f)`, returning the first item for which `f` is truthy or `null`.
+fn find(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, f] = unpack_method(args, 2)?;
+    for value in this.into_array()? {
+        let value = Value::from_rc(value);
+        if ctx.call(&f, vec![value.clone()])?.as_bool()? {
+            return Ok(value);
+        }
+    }
+    Ok(Value::Null)
+}
+
+/// `xs.sort(descending?)`, using the total ordering of values.
+fn sort(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, descending] = unpack_method(args, 1)?;
+    sort_by(ctx, vec![this, Value::Null, descending])
+}
+
+/// `xs.sort_by(key, descending?)`, where `key` is a property name or a function.
+/// 
+/// The sort is stable, so items with equal keys keep their order in both directions.
+fn sort_by(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, key, descending] = unpack_method(args, 2)?;
+    let descending = descending.as_bool()?;
+    let mut items = vec![];
+    for value in this.into_array()? {
+        items.push((key_of(ctx, &key, Value::from_rc(value.clone()))?, value));
+    }
+    items.sort_by(|(a, _), (b, _)| match descending {
+        true => b.total_cmp(a),
+        false => a.total_cmp(b),
+    });
+    Ok(Value::Array(items.into_iter().map(|(_, value)| value).collect()))
+}
+
+/// `xs.group_by(key)`, returning an object from keys to arrays of items in their original order.
+fn group_by(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, key] = unpack_method(args, 2)?;
+    let mut groups = BTreeMap::<String, Vec<_>>::new();
+    for value in this.into_array()? {
+        let key = key_of(ctx, &key, Value::from_rc(value.clone()))?.as_string()?;
+        groups.entry(key).or_default().push(value);
+    }
+    Ok(Value::Object(groups.into_iter().map(|(k, v)| (k, Value::Array(v).into_rc())).collect()))
+}
+
+/// `xs.unique(key?)`, keeping the first item of each distinct key.
+fn unique(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, key] = unpack_method(args, 1)?;
+    let mut keys = vec![];
+    let mut output = vec![];
+    for value in this.into_array()? {
+        let key = key_of(ctx, &key, Value::from_rc(value.clone()))?;
+        if !keys.contains(&key) {
+            keys.push(key);
+            output.push(value);
+        }
+    }
+    Ok(Value::Array(output))
+}
+
+/// `xs.sum(key?)`, which is `0` for an empty array.
+pub(super) fn sum(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, key] = unpack_method(args, 1)?;
+    let mut total = Value::Integer(0);
+    for value in this.into_array()? {
+        total = BinaryOp::Add.eval(total, key_of(ctx, &key, Value::from_rc(value))?, &ctx.config().number)?;
+    }
+    Ok(total)
+}
+
+/// `xs.avg(key?)`, which is `null` for an empty array.
+pub(super) fn avg(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, key] = unpack_method(args, 1)?;
+    let len = this.clone().into_array()?.len();
+    if len == 0 {
+        return Ok(Value::Null);
+    }
+    let total = sum(ctx, vec![this, key])?;
+    Ok(Value::Number(total.as_number()? / len as f64))
+}
+
+/// `xs.count(f?)`, counting the items for which `f` is truthy, or all items.
+pub(super) fn count(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, f] = unpack_method(args, 1)?;
+    let mut count = 0;
+    for value in this.into_array()? {
+        let matched = match f.deref() {
+            Value::Null => true,
+            _ => ctx.call(&f, vec![Value::from_rc(value)])?.as_bool()?,
+        };
+        if matched {
+            count += 1;
+        }
+    }
+    Ok(Value::from(count))
+}
+
+/// `xs.flatten(depth?)`, flattening nested arrays by one level by default.
+fn flatten(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, depth] = unpack_method(args, 1)?;
+    let depth = match depth.deref() {
+        Value::Null => 1,
+        _ => to_usize(&depth)?,
+    };
+    let mut items = this.into_array()?;
+    for _ in 0..depth {
+        if !items.iter().any(|value| matches!(value.deref(), Value::Array(_))) {
+            break;
+        }
+        items = items.into_iter().flat_map(|value| match value.deref() {
+            Value::Array(vec) => vec.clone(),
+            _ => vec![value],
+        }).collect();
+    }
+    Ok(Value::Array(items))
+}
+
+/// `xs.zip(...others)`, pairing items up to the length of the shortest array.
+fn zip(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let arrays = args.into_iter().map(Value::into_array).collect::<Result<Vec<_>, _>>()?;
+    let len = arrays.iter().map(Vec::len).min().unwrap_or(0);
+    Ok(Value::Array((0..len).map(|i| {
+        Value::Array(arrays.iter().map(|vec| vec[i].clone()).collect()).into_rc()
+    }).collect()))
+}
+
+/// `xs.chunk(size)`, splitting into arrays of `size` items, where the last may be shorter.
+fn chunk(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, size] = unpack_method(args, 2)?;
+    let size = to_usize(&size)?;
+    if size == 0 {
+        return Err(RuntimeError {
+            message: "chunk size must be positive".into(),
+        });
+    }
+    Ok(Value::Array(this.into_array()?.chunks(size).map(|chunk| {
+        Value::Array(chunk.to_vec()).into_rc()
+    }).collect()))
+}
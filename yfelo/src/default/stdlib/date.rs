@@ -0,0 +1,126 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{unpack, unpack_method, Context, DateTime, Native, RuntimeError, Value};
+
+pub fn method(name: &str) -> Option<Native> {
+    Some(match name {
+        "format" => format,
+        "add" => add,
+        "diff" => diff,
+        "to_offset" => to_offset,
+        "timestamp" => |_, args| field(args, |date| date.timestamp()),
+        "year" => |_, args| field(args, |date| date.fields().year),
+        "month" => |_, args| field(args, |date| date.fields().month.into()),
+        "day" => |_, args| field(args, |date| date.fields().day.into()),
+        "hour" => |_, args| field(args, |date| date.fields().hour.into()),
+        "minute" => |_, args| field(args, |date| date.fields().minute.into()),
+        "second" => |_, args| field(args, |date| date.fields().second.into()),
+        "weekday" => |_, args| field(args, |date| date.fields().weekday.into()),
+        _ => return None,
+    })
+}
+
+fn as_date(value: &Value) -> Result<DateTime, RuntimeError> {
+    match value.deref() {
+        Value::Date(date) => Ok(*date),
+        _ => Err(RuntimeError {
+            message: format!("expect date, found {}", value.type_name()),
+        }),
+    }
+}
+
+/// Resolve an offset given in minutes or as a string such as `+08:00`.
+fn as_offset(value: &Value) -> Result<i32, RuntimeError> {
+    match value.deref() {
+        Value::String(s) => DateTime::parse_offset(s),
+        _ => match value.as_integer()? {
+            n @ -1439..=1439 => Ok(n as i32),
+            _ => Err(RuntimeError {
+                message: format!("invalid offset '{}'", value),
+            }),
+        },
+    }
+}
+
+/// Milliseconds in a unit of time.
+fn as_unit(value: &Value) -> Result<i64, RuntimeError> {
+    let unit = match value.deref() {
+        Value::Null => return Ok(86_400_000),
+        _ => value.as_string()?,
+    };
+    Ok(match unit.as_str() {
+        "weeks" | "week" => 604_800_000,
+        "days" | "day" => 86_400_000,
+        "hours" | "hour" => 3_600_000,
+        "minutes" | "minute" => 60_000,
+        "seconds" | "second" => 1000,
+        "milliseconds" | "millisecond" | "ms" => 1,
+        _ => return Err(RuntimeError {
+            message: format!("invalid time unit '{}'", unit),
+        }),
+    })
+}
+
+fn field(args: Vec<Value>, f: fn(&DateTime) -> i64) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::Integer(f(&as_date(&this)?)))
+}
+
+/// `date(input, offset?)`, where `input` is an ISO-8601 string or milliseconds since the Unix epoch.
+pub fn date(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [input, offset] = unpack(args, 1)?;
+    let date = match input.deref() {
+        Value::Date(date) => *date,
+        Value::String(s) => DateTime::parse(s)?,
+        _ => DateTime::new(input.as_integer()?, 0)?,
+    };
+    Ok(Value::Date(match offset.deref() {
+        Value::Null => date,
+        _ => date.with_offset(as_offset(&offset)?)?,
+    }))
+}
+
+/// `now(offset?)`, the current time in UTC or the given offset.
+pub fn now(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [offset] = unpack(args, 0)?;
+    let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as i64,
+        Err(error) => -(error.duration().as_millis() as i64),
+    };
+    let offset = match offset.deref() {
+        Value::Null => 0,
+        _ => as_offset(&offset)?,
+    };
+    Ok(Value::Date(DateTime::new(timestamp, offset)?))
+}
+
+fn format(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, pattern] = unpack_method(args, 2)?;
+    Ok(Value::String(as_date(&this)?.format(&pattern.as_string()?)?))
+}
+
+/// `d.add(amount, unit?)`, where `unit` defaults to days.
+fn add(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, amount, unit] = unpack_method(args, 2)?;
+    let date = as_date(&this)?;
+    let delta = (amount.as_number()? * as_unit(&unit)? as f64).round();
+    let timestamp = Some(delta)
+        .filter(|delta| delta.is_finite() && delta.abs() < i64::MAX as f64)
+        .and_then(|delta| date.timestamp().checked_add(delta as i64))
+        .ok_or_else(|| RuntimeError {
+            message: "date out of range".into(),
+        })?;
+    Ok(Value::Date(DateTime::new(timestamp, date.offset())?))
+}
+
+/// `d.diff(other, unit?)`, the time from `other` to `d` in `unit`, which defaults to days.
+fn diff(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, other, unit] = unpack_method(args, 2)?;
+    let delta = as_date(&this)?.timestamp() as f64 - as_date(&other)?.timestamp() as f64;
+    Ok(Value::Number(delta / as_unit(&unit)? as f64))
+}
+
+fn to_offset(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, offset] = unpack_method(args, 2)?;
+    Ok(Value::Date(as_date(&this)?.with_offset(as_offset(&offset)?)?))
+}
@@ -0,0 +1,14 @@
+use std::collections::BTreeMap;
+
+use super::{unpack, Context, RuntimeError, Value};
+
+/// `t(key, params?)`, translating a key into the locale of the context.
+pub fn t(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [key, params] = unpack(args, 1)?;
+    let params = match params.deref() {
+        Value::Null => BTreeMap::new(),
+        _ => params.into_object()?,
+    };
+    let config = ctx.config();
+    Ok(Value::String(config.i18n.translate(&key.as_string()?, &params, &config.number)?))
+}
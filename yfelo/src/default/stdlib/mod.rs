@@ -0,0 +1,80 @@
+use super::{Context, DateTime, Native, NumberFormat, RuntimeError, Value};
+
+mod array;
+mod date;
+mod i18n;
+mod number;
+mod object;
+mod string;
+
+/// Look up a built-in function by name.
+/// 
+/// Built-ins are resolved after all scopes, so they can be shadowed by user bindings.
+pub fn global(name: &str) -> Option<Native> {
+    Some(match name {
+        "date" => date::date,
+        "now" => date::now,
+        "format_number" => number::format_number,
+        "t" => i18n::t,
+        _ => return None,
+    })
+}
+
+/// Look up a built-in method for the type of a value.
+/// 
+/// Methods are called with the receiver as the first argument.
+/// Properties of an object take precedence over its methods.
+pub fn method(value: &Value, name: &str) -> Option<Native> {
+    match value.deref() {
+        Value::Integer(_) | Value::Number(_) => number::method(name),
+        Value::Date(_) => date::method(name),
+        Value::String(_) => string::method(name),
+        Value::Array(_) => array::method(name),
+        Value::Object(map) if !map.contains_key(name) => object::method(name),
+        _ => None,
+    }
+}
+
+/// Unpack `N` positional arguments, of which the first `required` are mandatory.
+/// Missing optional arguments are filled with `null`.
+fn unpack<const N: usize>(args: Vec<Value>, required: usize) -> Result<[Value; N], RuntimeError> {
+    unpack_from(args, required, 0)
+}
+
+/// Unpack the arguments of a method like [`unpack`],
+/// except that the receiver is not counted in errors.
+fn unpack_method<const N: usize>(args: Vec<Value>, required: usize) -> Result<[Value; N], RuntimeError> {
+    unpack_from(args, required, 1)
+}
+
+fn unpack_from<const N: usize>(args: Vec<Value>, required: usize, skip: usize) -> Result<[Value; N], RuntimeError> {
+    if args.len() < required || args.len() > N {
+        let expected = match required == N {
+            true => format!("{}", N - skip),
+            false => format!("{} to {}", required - skip, N - skip),
+        };
+        return Err(RuntimeError {
+            message: format!("expect {} arguments, found {}", expected, args.len() - skip),
+        });
+    }
+    let mut iter = args.into_iter();
+    Ok(std::array::from_fn(|_| iter.next().unwrap_or(Value::Null)))
+}
+
+/// Convert an argument to a non-negative integer.
+fn to_usize(value: &Value) -> Result<usize, RuntimeError> {
+    let n = value.as_integer()?;
+    usize::try_from(n).map_err(|_| RuntimeError {
+        message: format!("expect non-negative integer, found {}", n),
+    })
+}
+
+/// Resolve the key of an item for sorting and grouping,
+/// where `key` is either a property name or a function.
+fn key_of(ctx: &Context, key: &Value, item: Value) -> Result<Value, RuntimeError> {
+    match key.deref() {
+        Value::Null => Ok(item),
+        Value::String(_) | Value::Integer(_) => item.get(key),
+        _ => ctx.call(key, vec![item]),
+    }
+}
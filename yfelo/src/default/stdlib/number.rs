@@ -0,0 +1,88 @@
+use super::{to_usize, unpack, unpack_method, Context, Native, NumberFormat, RuntimeError, Value};
+
+pub fn method(name: &str) -> Option<Native> {
+    Some(match name {
+        "abs" => abs,
+        "floor" => floor,
+        "ceil" => ceil,
+        "round" => round,
+        _ => return None,
+    })
+}
+
+/// Convert a float to an integer if it is integral and in range.
+fn to_integer(n: f64) -> Value {
+    match n.fract() == 0. && n >= i64::MIN as f64 && n < i64::MAX as f64 {
+        true => Value::Integer(n as i64),
+        false => Value::Number(n),
+    }
+}
+
+fn abs(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    match this.deref() {
+        Value::Integer(n) => n.checked_abs().map(Value::Integer).ok_or_else(|| RuntimeError {
+            message: "integer overflow".into(),
+        }),
+        _ => Ok(Value::Number(this.as_number()?.abs())),
+    }
+}
+
+fn floor(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    if let Value::Integer(_) = this.deref() {
+        return Ok(this);
+    }
+    Ok(to_integer(this.as_number()?.floor()))
+}
+
+fn ceil(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    if let Value::Integer(_) = this.deref() {
+        return Ok(this);
+    }
+    Ok(to_integer(this.as_number()?.ceil()))
+}
+
+/// `n.round(decimals?)`, rounding half away from zero.
+fn round(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, decimals] = unpack_method(args, 1)?;
+    let n = this.as_number()?;
+    match decimals.deref() {
+        Value::Null if matches!(this.deref(), Value::Integer(_)) => Ok(this),
+        Value::Null => Ok(to_integer(n.round())),
+        _ => {
+            let scale = 10f64.powi(decimals.as_integer()?.clamp(-308, 308) as i32);
+            Ok(Value::Number((n * scale).round() / scale))
+        },
+    }
+}
+
+/// `format_number(n, decimals?, thousands_sep?)`
+/// 
+/// Omitted options fall back to the number format of the context.
+pub fn format_number(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [n, decimals, sep] = unpack(args, 1)?;
+    let mut format = ctx.config().number.clone();
+    if !matches!(decimals.deref(), Value::Null) {
+        let decimals = to_usize(&decimals)?;
+        if decimals > NumberFormat::MAX_DECIMALS {
+            return Err(RuntimeError {
+                message: "decimals out of range".into(),
+            });
+        }
+        format.decimals = Some(decimals);
+    }
+    match sep.deref() {
+        Value::Null => {},
+        Value::String(s) if s.is_empty() => format.thousands = None,
+        Value::String(s) if s.chars().count() == 1 => format.thousands = s.chars().next(),
+        value => return Err(RuntimeError {
+            message: format!("expect a single character, found {}", value),
+        }),
+    }
+    Ok(Value::String(match n.deref() {
+        Value::Integer(n) if matches!(decimals.deref(), Value::Null) => format.format_integer(*n),
+        _ => format.format_number(n.as_number()?),
+    }))
+}
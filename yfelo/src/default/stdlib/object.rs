@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use super::{array, unpack_method, Context, Native, RuntimeError, Value};
+
+pub fn method(name: &str) -> Option<Native> {
+    Some(match name {
+        "len" => len,
+        "keys" => keys,
+        "values" => values,
+        "entries" => entries,
+        "map" => map,
+        "filter" => filter,
+        "sum" => |ctx, args| array::sum(ctx, with_values(args)?),
+        "avg" => |ctx, args| array::avg(ctx, with_values(args)?),
+        "count" => |ctx, args| array::count(ctx, with_values(args)?),
+        _ => return None,
+    })
+}
+
+fn len(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::from(this.into_object()?.len()))
+}
+
+fn keys(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::from(this.into_object()?.into_keys().map(Value::String).collect::<Vec<_>>()))
+}
+
+fn values(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::Array(this.into_object()?.into_values().collect()))
+}
+
+/// `obj.entries()`, returning an array of `[key, value]` pairs.
+fn entries(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::from(this.into_object()?.into_iter().map(|(k, v)| {
+        Value::Array(vec![Value::String(k).into_rc(), v])
+    }).collect::<Vec<_>>()))
+}
+
+/// `obj.map(f)`, mapping each value while keeping its key.
+fn map(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, f] = unpack_method(args, 2)?;
+    let mut output = BTreeMap::new();
+    for (key, value) in this.into_object()? {
+        output.insert(key, ctx.call(&f, vec![Value::from_rc(value)])?.into_rc());
+    }
+    Ok(Value::Object(output))
+}
+
+/// `obj.filter(f)`, keeping the entries for which `f` of the value is truthy.
+fn filter(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, f] = unpack_method(args, 2)?;
+    let mut output = BTreeMap::new();
+    for (key, value) in this.into_object()? {
+        if ctx.call(&f, vec![Value::from_rc(value.clone())])?.as_bool()? {
+            output.insert(key, value);
+        }
+    }
+    Ok(Value::Object(output))
+}
+
+/// Replace the receiver with an array of its values,
+/// so that `sum`, `avg` and `count` aggregate the values like their array counterparts.
+fn with_values(mut args: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+    if let Some(this) = args.first_mut() {
+        *this = Value::Array(this.clone().into_object()?.into_values().collect());
+    }
+    Ok(args)
+}
@@ -0,0 +1,146 @@
+use regex::Captures;
+
+use super::{to_usize, unpack_method, Context, Native, RuntimeError, Value};
+
+pub fn method(name: &str) -> Option<Native> {
+    Some(match name {
+        "len" => len,
+        "upper" => upper,
+        "lower" => lower,
+        "trim" => trim,
+        "split" => split,
+        "starts_with" => starts_with,
+        "ends_with" => ends_with,
+        "replace" => replace,
+        "repeat" => repeat,
+        "index_of" => index_of,
+        "matches" => matches,
+        "captures" => captures,
+        "regex_replace" => regex_replace,
+        "regex_split" => regex_split,
+        _ => return None,
+    })
+}
+
+fn len(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::from(this.as_string()?.chars().count()))
+}
+
+fn upper(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::String(this.as_string()?.to_uppercase()))
+}
+
+fn lower(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::String(this.as_string()?.to_lowercase()))
+}
+
+fn trim(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this] = unpack_method(args, 1)?;
+    Ok(Value::String(this.as_string()?.trim().to_string()))
+}
+
+/// `s.split(sep?)`, splitting on whitespace if `sep` is omitted.
+fn split(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, sep] = unpack_method(args, 1)?;
+    let this = this.as_string()?;
+    let parts: Vec<_> = match sep.deref() {
+        Value::Null => this.split_whitespace().map(Value::from).collect(),
+        _ => this.split(sep.as_string()?.as_str()).map(Value::from).collect(),
+    };
+    Ok(Value::from(parts))
+}
+
+fn starts_with(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, prefix] = unpack_method(args, 2)?;
+    Ok(Value::Bool(this.as_string()?.starts_with(&prefix.as_string()?)))
+}
+
+fn ends_with(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, suffix] = unpack_method(args, 2)?;
+    Ok(Value::Bool(this.as_string()?.ends_with(&suffix.as_string()?)))
+}
+
+fn replace(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, from, to] = unpack_method(args, 3)?;
+    Ok(Value::String(this.as_string()?.replace(&from.as_string()?, &to.as_string()?)))
+}
+
+/// The longest string in bytes that `s.repeat(n)` may produce.
+const MAX_REPEAT_LEN: usize = 1 << 24;
+
+fn repeat(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, n] = unpack_method(args, 2)?;
+    let (this, n) = (this.as_string()?, to_usize(&n)?);
+    match this.len().checked_mul(n) {
+        Some(len) if len <= MAX_REPEAT_LEN => Ok(Value::String(this.repeat(n))),
+        _ => Err(RuntimeError {
+            message: "repeated string too long".into(),
+        }),
+    }
+}
+
+/// `s.index_of(sub)`, returning the character index of the first match or `-1`.
+fn index_of(_: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, sub] = unpack_method(args, 2)?;
+    let this = this.as_string()?;
+    Ok(match this.find(&sub.as_string()?) {
+        Some(i) => Value::from(this[..i].chars().count()),
+        None => Value::Integer(-1),
+    })
+}
+
+/// `s.matches(pattern)`, checking whether the regex matches anywhere in the string.
+fn matches(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, pattern] = unpack_method(args, 2)?;
+    let regex = ctx.regex(&pattern.as_string()?)?;
+    Ok(Value::Bool(regex.is_match(&this.as_string()?)))
+}
+
+fn to_array(captures: &Captures) -> Value {
+    Value::from(captures.iter().map(|group| match group {
+        Some(group) => Value::from(group.as_str()),
+        None => Value::Null,
+    }).collect::<Vec<_>>())
+}
+
+/// `s.captures(pattern)`, returning the whole match and its groups of the first match, or `null`.
+fn captures(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, pattern] = unpack_method(args, 2)?;
+    let regex = ctx.regex(&pattern.as_string()?)?;
+    Ok(match regex.captures(&this.as_string()?) {
+        Some(captures) => to_array(&captures),
+        None => Value::Null,
+    })
+}
+
+/// `s.regex_replace(pattern, replacement)`, replacing all matches.
+/// 
+/// The replacement is either a string which may refer to groups as `$1` or `${name}`,
+/// or a function called with the array of captures.
+fn regex_replace(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, pattern, replacement] = unpack_method(args, 3)?;
+    let regex = ctx.regex(&pattern.as_string()?)?;
+    let this = this.as_string()?;
+    if let Value::Abs(..) | Value::Native(_) = replacement.deref() {
+        let mut output = String::new();
+        let mut last = 0;
+        for captures in regex.captures_iter(&this) {
+            let range = captures.get(0).unwrap().range();
+            output += &this[last..range.start];
+            output += &ctx.call(&replacement, vec![to_array(&captures)])?.as_string()?;
+            last = range.end;
+        }
+        return Ok(Value::String(output + &this[last..]));
+    }
+    Ok(Value::String(regex.replace_all(&this, replacement.as_string()?.as_str()).into_owned()))
+}
+
+/// `s.regex_split(pattern)`, splitting the string by matches of the regex.
+fn regex_split(ctx: &Context, args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let [this, pattern] = unpack_method(args, 2)?;
+    let regex = ctx.regex(&pattern.as_string()?)?;
+    Ok(Value::from(regex.split(&this.as_string()?).map(Value::from).collect::<Vec<_>>()))
+}
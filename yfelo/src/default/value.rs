@@ -1,20 +1,27 @@
+use std::cmp::Ordering;
 use std::{collections::BTreeMap, ops::Deref};
 use std::rc::Rc;
 use std::fmt;
 
 use yfelo_core::{factory, Definition};
 
-use super::{Expr, Pattern, RuntimeError};
+use super::{Context, ContextInner, DateTime, Expr, NumberFormat, Pattern, RuntimeError};
+
+/// A function implemented in Rust, called with positional arguments only.
+pub type Native = fn(&Context, Vec<Value>) -> Result<Value, RuntimeError>;
 
 #[derive(Debug, Clone)]
 pub enum Value {
     Null,
     Bool(bool),
+    Integer(i64),
     Number(f64),
+    Date(DateTime),
     String(String),
     Array(Vec<Rc<Value>>),
     Object(BTreeMap<String, Rc<Value>>),
-    Abs(Vec<(Pattern, Option<Expr>)>, Definition),
+    Abs(Vec<(Pattern, Option<Expr>)>, Definition, Rc<ContextInner>),
+    Native(Native),
     Ref(Rc<Value>),
 }
 
@@ -30,17 +37,19 @@ impl Value {
         match &self {
             Self::Null => "null",
             Self::Bool(_) => "bool",
-            Self::Number(_) => "number",
+            Self::Integer(_) | Self::Number(_) => "number",
+            Self::Date(_) => "date",
             Self::String(_) => "string",
             Self::Array(_) => "array",
             Self::Object(_) => "object",
-            Self::Abs(_, _) => "function",
+            Self::Abs(..) | Self::Native(_) => "function",
             Self::Ref(v) => v.type_name(),
         }
     }
 
     pub fn as_number(&self) -> Result<f64, RuntimeError> {
         match self.deref() {
+            Self::Integer(n) => Ok(*n as f64),
             Self::Number(n) => Ok(*n),
             // Self::Bool(b) => Ok(if *b { 1. } else { 0. }),
             _ => Err(RuntimeError {
@@ -49,9 +58,117 @@ impl Value {
         }
     }
 
+    /// Numbers with integral values are also accepted.
+    pub fn as_integer(&self) -> Result<i64, RuntimeError> {
+        match self.deref() {
+            Self::Integer(n) => Ok(*n),
+            Self::Number(n) if n.fract() == 0. && *n >= i64::MIN as f64 && *n < i64::MAX as f64 => Ok(*n as i64),
+            _ => Err(RuntimeError {
+                message: format!("expect integer, found {}", self.type_name()),
+            }),
+        }
+    }
+
+    /// Compare two numbers, where integers are compared without loss of precision.
+    pub fn cmp_number(&self, other: &Self) -> Result<Option<Ordering>, RuntimeError> {
+        match (self.deref(), other.deref()) {
+            (Self::Integer(a), Self::Integer(b)) => Ok(Some(a.cmp(b))),
+            _ => Ok(self.as_number()?.partial_cmp(&other.as_number()?)),
+        }
+    }
+
+    /// Compare numbers numerically, dates chronologically, strings lexicographically by code point,
+    /// and arrays element by element. `None` means the values are unordered (e.g. NaN).
+    pub fn compare(&self, other: &Self) -> Result<Option<Ordering>, RuntimeError> {
+        match (self.deref(), other.deref()) {
+            (Self::Integer(_) | Self::Number(_), Self::Integer(_) | Self::Number(_)) => self.cmp_number(other),
+            (Self::Date(a), Self::Date(b)) => Ok(Some(a.timestamp().cmp(&b.timestamp()))),
+            (Self::String(a), Self::String(b)) => Ok(Some(a.cmp(b))),
+            (Self::Array(a), Self::Array(b)) => {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    match a.compare(b)? {
+                        Some(Ordering::Equal) => continue,
+                        ordering => return Ok(ordering),
+                    }
+                }
+                Ok(Some(a.len().cmp(&b.len())))
+            },
+            _ => Err(RuntimeError {
+                message: format!("cannot compare {} and {}", self.type_name(), other.type_name()),
+            }),
+        }
+    }
+
+    /// A total ordering over all values, suitable for sorting.
+    /// 
+    /// Values of different types are ordered by type: null, bool, number, date, string, array, object and function.
+    /// Numbers compare by value with `NaN` sorting after all other numbers,
+    /// and arrays and objects compare element by element. All functions are considered equal.
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self.deref(), other.deref()) {
+            (a @ (Self::Integer(_) | Self::Number(_)), b @ (Self::Integer(_) | Self::Number(_))) => {
+                match a.cmp_number(b) {
+                    Ok(Some(ordering)) => ordering,
+                    _ => a.is_nan().cmp(&b.is_nan()),
+                }
+            },
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Date(a), Self::Date(b)) => a.timestamp().cmp(&b.timestamp()),
+            (Self::String(a), Self::String(b)) => a.cmp(b),
+            (Self::Array(a), Self::Array(b)) => {
+                a.iter().zip(b.iter())
+                    .map(|(a, b)| a.total_cmp(b))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| a.len().cmp(&b.len()))
+            },
+            (Self::Object(a), Self::Object(b)) => {
+                a.iter().zip(b.iter())
+                    .map(|((k1, v1), (k2, v2))| k1.cmp(k2).then_with(|| v1.total_cmp(v2)))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| a.len().cmp(&b.len()))
+            },
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self.deref() {
+            Self::Null => 0,
+            Self::Bool(_) => 1,
+            Self::Integer(_) | Self::Number(_) => 2,
+            Self::Date(_) => 3,
+            Self::String(_) => 4,
+            Self::Array(_) => 5,
+            Self::Object(_) => 6,
+            Self::Abs(..) | Self::Native(_) | Self::Ref(_) => 7,
+        }
+    }
+
+    fn is_nan(&self) -> bool {
+        matches!(self.deref(), Self::Number(n) if n.is_nan())
+    }
+
+    /// Check whether an array has an element, an object has a key, or a string has a substring.
+    pub fn contains(&self, item: &Self) -> Result<bool, RuntimeError> {
+        match self.deref() {
+            Self::Array(vec) => Ok(vec.iter().any(|value| value.as_ref() == item)),
+            Self::Object(map) => Ok(map.contains_key(&item.as_string()?)),
+            Self::String(s) => Ok(s.contains(&item.as_string()?)),
+            _ => Err(RuntimeError {
+                message: format!("expect array, object or string, found {}", self.type_name()),
+            }),
+        }
+    }
+
     pub fn as_string(&self) -> Result<String, RuntimeError> {
+        self.as_string_with(&NumberFormat::default())
+    }
+
+    /// Convert a string, number or date to a string, formatting numbers with `number`.
+    pub fn as_string_with(&self, number: &NumberFormat) -> Result<String, RuntimeError> {
         match self.deref() {
-            Self::Number(n) => Ok(n.to_string()),
+            Self::Integer(_) | Self::Number(_) => Ok(self.format(number)),
+            Self::Date(d) => Ok(d.to_string()),
             Self::String(s) => Ok(s.clone()),
             _ => Err(RuntimeError {
                 message: format!("expect string, found {}", self.type_name()),
@@ -63,15 +180,24 @@ impl Value {
         match self.deref() {
             Self::Null => Ok(false),
             Self::Bool(b) => Ok(*b),
+            Self::Integer(n) => Ok(*n != 0),
             Self::Number(n) => Ok(*n != 0.),
             Self::String(s) => Ok(!s.is_empty()),
             _ => Ok(true),
         }
     }
 
-    pub fn as_abs(&self) -> Result<(&Vec<(Pattern, Option<Expr>)>, &Definition), RuntimeError> {
+    /// Format the value for interpolation, where `null` renders as nothing.
+    pub fn format(&self, number: &NumberFormat) -> String {
+        match self.deref() {
+            Self::Null => String::new(),
+            value => Formatted(value, number).to_string(),
+        }
+    }
+
+    pub fn as_abs(&self) -> Result<(&Vec<(Pattern, Option<Expr>)>, &Definition, &Rc<ContextInner>), RuntimeError> {
         match self.deref() {
-            Self::Abs(params, definition) => Ok((params, definition)),
+            Self::Abs(params, definition, scope) => Ok((params, definition, scope)),
             _ => Err(RuntimeError {
                 message: format!("expect function, found {}", self.type_name()),
             }),
@@ -81,6 +207,7 @@ impl Value {
     pub fn into_array(self) -> Result<Vec<Rc<Value>>, RuntimeError> {
         match self {
             Self::Array(vec) => Ok(vec),
+            Self::Ref(v) => v.as_ref().clone().into_array(),
             _ => Err(RuntimeError {
                 message: format!("expect array, found {}", self.type_name()),
             }),
@@ -90,6 +217,7 @@ impl Value {
     pub fn into_object(self) -> Result<BTreeMap<String, Rc<Value>>, RuntimeError> {
         match self {
             Self::Object(map) => Ok(map),
+            Self::Ref(v) => v.as_ref().clone().into_object(),
             _ => Err(RuntimeError {
                 message: format!("expect object, found {}", self.type_name()),
             }),
@@ -120,16 +248,79 @@ impl Value {
                 }
             },
             Self::Array(vec) => {
-                match vec.get(key.as_number()? as usize) {
+                match to_index(key.as_integer()?, vec.len()).and_then(|index| vec.get(index)) {
                     Some(rc) => Ok(Self::Ref(rc.clone())),
                     None => Ok(Self::Null),
                 }
             },
+            Self::String(s) => {
+                if !matches!(key.deref(), Self::Integer(_) | Self::Number(_)) {
+                    return Err(RuntimeError {
+                        message: format!("cannot index into string with {}", key.type_name()),
+                    });
+                }
+                let len = s.chars().count();
+                match to_index(key.as_integer()?, len).and_then(|index| s.chars().nth(index)) {
+                    Some(c) => Ok(Self::String(c.to_string())),
+                    None => Ok(Self::Null),
+                }
+            },
             _ => Err(RuntimeError {
                 message: format!("cannot index into {}", self.type_name()),
             }),
         }
     }
+
+    /// Slice an array or a string (by character) from `start` to `end`, exclusive.
+    /// 
+    /// Negative bounds count from the end, and out-of-range bounds are clamped,
+    /// so the result is empty rather than an error when `start` is not before `end`.
+    pub fn slice(&self, start: Option<&Self>, end: Option<&Self>) -> Result<Self, RuntimeError> {
+        let bound = |value: Option<&Self>, len: usize, default: usize| match value.map(Self::deref) {
+            None | Some(Self::Null) => Ok(default),
+            Some(value) => {
+                let index = value.as_integer()?;
+                let len = len as i64;
+                let index = if index < 0 { len + index } else { index };
+                Ok(index.clamp(0, len) as usize)
+            },
+        };
+        match self.deref() {
+            Self::Array(vec) => {
+                let start = bound(start, vec.len(), 0)?;
+                let end = bound(end, vec.len(), vec.len())?.max(start);
+                Ok(Self::Array(vec[start..end].to_vec()))
+            },
+            Self::String(s) => {
+                let len = s.chars().count();
+                let start = bound(start, len, 0)?;
+                let end = bound(end, len, len)?.max(start);
+                Ok(Self::String(s.chars().skip(start).take(end - start).collect()))
+            },
+            _ => Err(RuntimeError {
+                message: format!("cannot slice {}", self.type_name()),
+            }),
+        }
+    }
+}
+
+/// Resolve an index which may count from the end, returning `None` if it is out of range.
+fn to_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { index + len as i64 } else { index };
+    usize::try_from(index).ok().filter(|index| *index < len)
+}
+
+macro_rules! impl_from_integer {
+    ($ty:ty) => {
+        impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                match i64::try_from(value) {
+                    Ok(value) => Value::Integer(value),
+                    Err(_) => Value::Number(value as f64),
+                }
+            }
+        }
+    };
 }
 
 macro_rules! impl_from_number {
@@ -142,18 +333,18 @@ macro_rules! impl_from_number {
     };
 }
 
-impl_from_number!(u8);
-impl_from_number!(u16);
-impl_from_number!(u32);
-impl_from_number!(u64);
-impl_from_number!(u128);
-impl_from_number!(usize);
-impl_from_number!(i8);
-impl_from_number!(i16);
-impl_from_number!(i32);
-impl_from_number!(i64);
-impl_from_number!(i128);
-impl_from_number!(isize);
+impl_from_integer!(u8);
+impl_from_integer!(u16);
+impl_from_integer!(u32);
+impl_from_integer!(u64);
+impl_from_integer!(u128);
+impl_from_integer!(usize);
+impl_from_integer!(i8);
+impl_from_integer!(i16);
+impl_from_integer!(i32);
+impl_from_integer!(i64);
+impl_from_integer!(i128);
+impl_from_integer!(isize);
 impl_from_number!(f32);
 impl_from_number!(f64);
 
@@ -176,66 +367,85 @@ impl From<&str> for Value {
     }
 }
 
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Self::Array(value.into_iter().map(Value::into_rc).collect())
+    }
+}
+
+/// Structural equality, following references.
+/// 
+/// Integers and numbers are equal if they have the same value, and dates if they are the same instant.
+/// `NaN` is not equal to anything, including itself. Functions are only equal to themselves.
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
+        match (self.deref(), other.deref()) {
             (Self::Null, Self::Null) => true,
             (Self::Bool(a), Self::Bool(b)) => a == b,
-            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::Integer(a), Self::Integer(b)) => a == b,
+            (a @ (Self::Integer(_) | Self::Number(_)), b @ (Self::Integer(_) | Self::Number(_))) => {
+                matches!(a.cmp_number(b), Ok(Some(Ordering::Equal)))
+            },
+            (Self::Date(a), Self::Date(b)) => a.timestamp() == b.timestamp(),
             (Self::String(a), Self::String(b)) => a == b,
-            (Self::Ref(a), b) => a.as_ref() == b,
-            (a, Self::Ref(b)) => a == b.as_ref(),
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Object(a), Self::Object(b)) => a == b,
+            (Self::Abs(a, c, e), Self::Abs(b, d, f)) => a == b && c == d && Rc::ptr_eq(e, f),
+            (Self::Native(a), Self::Native(b)) => std::ptr::fn_addr_eq(*a, *b),
             _ => false,
         }
     }
 }
 
+struct Formatted<'a>(&'a Value, &'a NumberFormat);
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &self {
-            Self::Null => write!(f, "null"),
-            Self::Bool(b) => write!(f, "{}", b),
-            Self::Number(n) => write!(f, "{}", n),
-            Self::String(s) => write!(f, "{}", s),
-            Self::Array(vec) => {
+        write!(f, "{}", Formatted(self, &NumberFormat::default()))
+    }
+}
+
+impl fmt::Display for Formatted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Self(value, number) = self;
+        match value {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(n) => write!(f, "{}", number.format_integer(*n)),
+            Value::Number(n) => write!(f, "{}", number.format_number(*n)),
+            Value::Date(d) => write!(f, "{}", d),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Array(vec) => {
                 write!(f, "[")?;
                 for (i, value) in vec.iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "{}", value)?;
+                    write!(f, "{}", Formatted(value, number))?;
                 }
                 write!(f, "]")
             },
-            Self::Object(map) => {
+            Value::Object(map) => {
                 write!(f, "{{")?;
                 for (i, (k, v)) in map.iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    write!(f, "{}: {}", k, v)?;
+                    write!(f, "{}: {}", k, Formatted(v, number))?;
                 }
                 write!(f, "}}")
             },
-            Self::Abs(_, _) => {
+            Value::Abs(..) | Value::Native(_) => {
                 write!(f, "fn")
             },
-            Self::Ref(v) => {
-                write!(f, "{}", v)
+            Value::Ref(v) => {
+                write!(f, "{}", Formatted(v, number))
             },
         }
     }
 }
 
 impl factory::Value<RuntimeError> for Value {
-    fn to_string(&self) -> Result<String, RuntimeError> {
-        match self.deref() {
-            Self::Null => Ok("".to_string()),
-            // TODO partial object
-            _ => Ok(format!("{}", self)),
-        }
-    }
-
     fn as_bool(&self) -> Result<bool, RuntimeError> {
         Self::as_bool(self)
     }
@@ -243,7 +453,7 @@ impl factory::Value<RuntimeError> for Value {
     fn as_entries(&self) -> Result<Vec<(Self, Self)>, RuntimeError> {
         match self.deref() {
             Self::Array(vec) => Ok(vec.iter().enumerate().map(|(k, v)| {
-                let k = Self::Number(k as f64);
+                let k = Self::from(k);
                 (Self::Ref(v.clone()), k)
             }).collect()),
             Self::Object(map) => Ok(map.iter().map(|(k, v)| {
@@ -0,0 +1,211 @@
+mod common;
+
+use common::{header, runtime_error};
+use dyn_std::Instance;
+use yfelo::SyntaxError;
+use yfelo::default::Context;
+
+#[test]
+pub fn parse_1() {
+    let error = header().parse("{#apply card}{:slot}{/apply}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "invalid syntax for directive 'slot': expect identifier".into(),
+        range: (19, 19),
+    });
+}
+
+#[test]
+pub fn parse_2() {
+    let error = header().parse("{#if x}{:slot foo}{/if}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "unknown directive 'if:slot'".into(),
+        range: (9, 13),
+    });
+}
+
+#[test]
+pub fn render_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#def card(title)}
+            <h1>{title}</h1><p>{children}</p>
+        {/def}
+        {@def name = 'yfelo'}
+        {#apply card('Hello')}
+            Welcome to {name}!
+        {/apply}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "<h1>Hello</h1><p>Welcome to yfelo!</p>");
+}
+
+#[test]
+pub fn render_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#def layout}
+            <header>{slots.header}</header><main>{children}</main><footer>{slots.footer}</footer>
+        {/def}
+        {#apply layout}
+            Content
+        {:slot header}
+            Title
+        {:slot footer}
+            Copyright
+        {/apply}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "<header>Title</header><main>Content</main><footer>Copyright</footer>");
+}
+
+#[test]
+pub fn render_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#def card(children = 'default')}
+            [{children}]
+        {/def}
+        {#def plain}
+            [{children ?? 'none'}]
+        {/def}
+        {@apply card}{@apply plain}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[default][none]");
+    let error = header().render("
+        {#def panel(children)}{/def}
+        {#apply panel}
+            ignored
+        {/apply}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "parameter 'children' conflicts with block content");
+}
+
+#[test]
+pub fn render_4() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {#def card}{/def}
+        {#apply card}
+        {:slot header}
+        {:slot header}
+        {/apply}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "duplicate slot 'header'");
+}
+
+#[test]
+pub fn named_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#def button(text, size = 'md', color = 'blue')}
+            [{text}:{size}:{color}]
+        {/def}
+        {@apply button('OK', color = 'red')}
+        {@apply button(size = 'lg', text = 'Cancel')}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[OK:md:red][Cancel:lg:blue]");
+}
+
+#[test]
+pub fn named_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def f = (a, b = 2, c = a + b) => [a, b, c]}
+        {f(1, c = 0)} {f(a = 1 == 1, b = x => x, c = 0)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[1, 2, 0] [true, fn, 0]");
+}
+
+#[test]
+pub fn named_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {#def f(a)}{/def}
+        {@apply f(b = 1)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "unknown argument 'b'");
+}
+
+#[test]
+pub fn named_4() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {#def f(a)}{/def}
+        {@apply f(1, a = 2)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "duplicate argument 'a'");
+}
+
+#[test]
+pub fn named_5() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {@def f = (a, b) => a - b}
+        {f(b = 1, 2)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "positional argument follows named argument");
+}
+
+#[test]
+pub fn rest_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#def list(title, ...items)}
+            {title}: {items}
+        {/def}
+        {@apply list('a')} {@apply list('b', 1, 2, 3)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "a: [] b: [1, 2, 3]");
+}
+
+#[test]
+pub fn rest_2() {
+    let error = header().parse("{@def ...x = [1, 2]}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "invalid syntax for directive 'def': unexpected rest pattern '...x'".into(),
+        range: (6, 10),
+    });
+}
+
+#[test]
+pub fn rest_3() {
+    let error = header().parse("{@def f(...x, y) = x}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "invalid syntax for directive 'def': rest parameter must be last".into(),
+        range: (14, 14),
+    });
+    let error = header().parse("{@def f = (...x, y) => x}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "invalid syntax for directive 'def': rest parameter must be last".into(),
+        range: (11, 15),
+    });
+}
+
+#[test]
+pub fn spread_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def f = (a, b, c = 0, ...d) => [a, b, c, d]}
+        {@def xs = [1, 2]}
+        {f(...xs)} {f(0, ...xs, 3, 4)} {f(...{b: 1, a: 2})}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[1, 2, 0, []] [0, 1, 2, [3, 4]] [2, 1, 0, []]");
+}
+
+#[test]
+pub fn spread_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {@def f = x => x}
+        {f(...1)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect array or object, found number");
+}
+
+#[test]
+pub fn pattern_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {#def f({a, 'b': [c = 1, ...d], ...e})}{/def}
+        {@apply f}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "missing required argument '{a, \"b\": [c = 1, ...d], ...e}'");
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::header;
+use dyn_std::Instance;
+use yfelo::SyntaxError;
+use yfelo::default::Context;
+
+#[test]
+pub fn parse_1() {
+    let error = header().parse("{@capture x}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "directive 'capture' should not be empty".into(),
+        range: (2, 9),
+    });
+}
+
+#[test]
+pub fn parse_2() {
+    let error = header().parse("{#capture}{/capture}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "invalid syntax for directive 'capture': expect pattern".into(),
+        range: (9, 9),
+    });
+}
+
+#[test]
+pub fn render_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def name = 'yfelo'}
+        {#capture title}
+            Hello, {name}!
+        {/capture}
+        <title>{title}</title><h1>{title}</h1>
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "<title>Hello, yfelo!</title><h1>Hello, yfelo!</h1>");
+}
+
+#[test]
+pub fn render_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#capture list}
+            {#for x in [1, 2, 3]}
+                {@def y = x * 2}
+                {y}
+            {/for}
+        {/capture}
+        [{list}]
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[246]");
+}
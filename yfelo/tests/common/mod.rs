@@ -0,0 +1,18 @@
+#![allow(dead_code)]
+
+use dyn_std::Instance;
+use yfelo::{Error, Header, Yfelo};
+use yfelo::default::{Expr, Language, Pattern, RuntimeError};
+
+pub fn header() -> Header<'static, 'static> {
+    let yfelo = Box::leak(Box::new(Yfelo::new()));
+    yfelo.add_language::<Expr, Pattern, Language>("default");
+    yfelo.prepare("{@yfelo}", false).unwrap()
+}
+
+pub fn runtime_error(error: Error) -> RuntimeError {
+    match error {
+        Error::Runtime(e) => e.as_any_box().downcast::<Instance<RuntimeError, ()>>().unwrap().0,
+        Error::Syntax(e) => panic!("unexpected syntax error: {:?}", e),
+    }
+}
@@ -1,18 +1,14 @@
+mod common;
+
+use common::header;
 use dyn_std::Instance;
-use once_cell::sync::Lazy;
 use yfelo::builtin::For;
-use yfelo::{Element, Header, Node, SyntaxError, Yfelo};
-use yfelo::default::{Context, Expr, Language, Pattern};
-
-const HEADER: Lazy<Header> = Lazy::new(|| {
-    let yfelo = Box::leak(Box::new(Yfelo::new()));
-    yfelo.add_language::<Expr, Pattern, Language>("default");
-    yfelo.prepare("{@yfelo}", false).unwrap()
-});
+use yfelo::{Element, Node, SyntaxError};
+use yfelo::default::{Context, Expr, Pattern};
 
 #[test]
 pub fn parse_1() {
-    let error = HEADER.parse("{#for}").unwrap_err();
+    let error = header().parse("{#for}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid syntax for directive 'for': expect pattern".into(),
         range: (5, 5),
@@ -21,7 +17,7 @@ pub fn parse_1() {
 
 #[test]
 pub fn parse_2() {
-    let error = HEADER.parse("{#for x}").unwrap_err();
+    let error = header().parse("{#for x}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid syntax for directive 'for': expect keyword 'in'".into(),
         range: (7, 7),
@@ -30,7 +26,7 @@ pub fn parse_2() {
 
 #[test]
 pub fn parse_3() {
-    let error = HEADER.parse("{@for}").unwrap_err();
+    let error = header().parse("{@for}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "directive 'for' should not be empty".into(),
         range: (2, 5),
@@ -39,7 +35,7 @@ pub fn parse_3() {
 
 #[test]
 pub fn parse_4() {
-    let error = HEADER.parse("{#for x in}").unwrap_err();
+    let error = header().parse("{#for x in}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid syntax for directive 'for': expect expression".into(),
         range: (10, 10),
@@ -48,7 +44,7 @@ pub fn parse_4() {
 
 #[test]
 pub fn parse_5() {
-    let error = HEADER.parse("{#for x in y z}").unwrap_err();
+    let error = header().parse("{#for x in y z}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid tag syntax: expect '}'".into(),
         range: (13, 13),
@@ -57,7 +53,7 @@ pub fn parse_5() {
 
 #[test]
 pub fn parse_6() {
-    let error = HEADER.parse("{#for x, y, z in w}").unwrap_err();
+    let error = header().parse("{#for x, y, z in w}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid syntax for directive 'for': expect keyword 'in'".into(),
         range: (10, 10),
@@ -66,7 +62,7 @@ pub fn parse_6() {
 
 #[test]
 pub fn parse_7() {
-    let nodes = HEADER.parse("{#for x in y}{/for}").unwrap();
+    let nodes = header().parse("{#for x in y}{/for}").unwrap();
     assert_eq!(nodes, vec![
         Node::Element(Element {
             directive: Box::new(Instance::new(For {
@@ -82,7 +78,7 @@ pub fn parse_7() {
 
 #[test]
 pub fn parse_8() {
-    let nodes = HEADER.parse("{#for x, y in z}{/for}").unwrap();
+    let nodes = header().parse("{#for x, y in z}{/for}").unwrap();
     assert_eq!(nodes, vec![
         Node::Element(Element {
             directive: Box::new(Instance::new(For {
@@ -99,7 +95,7 @@ pub fn parse_8() {
 #[test]
 pub fn render_1() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#for a in [1, 2, 3]}
             {a * 2}
         {/for}
@@ -110,7 +106,7 @@ pub fn render_1() {
 #[test]
 pub fn render_2() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#for a, b in [1, 2, 3]}
             {b + 1}. {a * 2}
         {/for}
@@ -121,7 +117,7 @@ pub fn render_2() {
 #[test]
 pub fn render_3() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#for x in {x: 2, y: 3, z: 1}}
             {x}
         {/for}
@@ -132,7 +128,7 @@ pub fn render_3() {
 #[test]
 pub fn render_4() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#for x, y in {x: 2, y: 3, z: 1}}
             {y}. {x}
         {/for}
@@ -0,0 +1,74 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{header, runtime_error};
+use dyn_std::Instance;
+use yfelo::default::{Catalog, Config, Context, I18n, MissingKey};
+
+fn context(locale: &str, missing: MissingKey) -> Box<dyn yfelo::Context> {
+    let en = Catalog::from_json(r#"{
+        "greeting": "Hello, {name}!",
+        "inbox": { "=0": "No messages", "one": "{count} message", "other": "{count} messages" },
+        "nav": { "home": "Home", "about": "About" },
+        "menu": { "home": "Home", "other": "Other" }
+    }"#).unwrap();
+    let ru = Catalog::from_json(r#"{
+        "greeting": "Привет, {name}!",
+        "inbox": { "one": "{count} сообщение", "few": "{count} сообщения", "many": "{count} сообщений", "other": "{count} сообщения" }
+    }"#).unwrap();
+    Box::new(Instance::new(Context::with_config(Config {
+        i18n: I18n {
+            locale: locale.into(),
+            fallback: Some("en".into()),
+            catalogs: HashMap::from([("en".into(), en), ("ru".into(), ru)]),
+            missing,
+        },
+        ..Default::default()
+    })))
+}
+
+#[test]
+pub fn basic_1() {
+    let mut ctx = context("en", MissingKey::Key);
+    let output = header().render("
+        {t('greeting', { name: 'Yfelo' })} {t('nav.home')} {t('nav.contact')} {t('menu.home')} {t('menu.other')}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "Hello, Yfelo! Home nav.contact Home Other");
+}
+
+#[test]
+pub fn plural_1() {
+    let mut ctx = context("en", MissingKey::Key);
+    let output = header().render("
+        {#for n in [0, 1, 2]}{t('inbox', { count: n })}; {/for}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "No messages; 1 message; 2 messages; ");
+}
+
+#[test]
+pub fn plural_2() {
+    let mut ctx = context("ru-RU", MissingKey::Key);
+    let output = header().render("
+        {#for n in [1, 3, 5, 11, 21, 22]}{t('inbox', { count: n })}; {/for}{t('nav.about')}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "1 сообщение; 3 сообщения; 5 сообщений; 11 сообщений; 21 сообщение; 22 сообщения; About");
+}
+
+#[test]
+pub fn missing_1() {
+    let mut ctx = context("en", MissingKey::Empty);
+    let output = header().render("
+        [{t('unknown')}]
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[]");
+    let mut ctx = context("fr", MissingKey::Error);
+    let error = header().render("
+        {t('unknown')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "missing translation for 'unknown' in locale 'fr'");
+    let error = header().render("
+        {t('inbox')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "missing count for plural message 'inbox'");
+}
@@ -1,17 +1,13 @@
-use dyn_std::Instance;
-use once_cell::sync::Lazy;
-use yfelo::{Header, SyntaxError, Yfelo};
-use yfelo::default::{Context, Expr, Language, Pattern};
+mod common;
 
-const HEADER: Lazy<Header> = Lazy::new(|| {
-    let yfelo = Box::leak(Box::new(Yfelo::new()));
-    yfelo.add_language::<Expr, Pattern, Language>("default");
-    yfelo.prepare("{@yfelo}", false).unwrap()
-});
+use common::header;
+use dyn_std::Instance;
+use yfelo::SyntaxError;
+use yfelo::default::Context;
 
 #[test]
 pub fn parse_1() {
-    let error = HEADER.parse("{#if}").unwrap_err();
+    let error = header().parse("{#if}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid syntax for directive 'if': expect expression".into(),
         range: (4, 4),
@@ -20,7 +16,7 @@ pub fn parse_1() {
 
 #[test]
 pub fn parse_2() {
-    let error = HEADER.parse("{#if x x}").unwrap_err();
+    let error = header().parse("{#if x x}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid tag syntax: expect '}'".into(),
         range: (7, 7),
@@ -29,7 +25,7 @@ pub fn parse_2() {
 
 #[test]
 pub fn parse_3() {
-    let error = HEADER.parse("{@if}").unwrap_err();
+    let error = header().parse("{@if}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "directive 'if' should not be empty".into(),
         range: (2, 4),
@@ -38,7 +34,7 @@ pub fn parse_3() {
 
 #[test]
 pub fn parse_4() {
-    let error = HEADER.parse("{#if x}{:else y}").unwrap_err();
+    let error = header().parse("{#if x}{:else y}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid tag syntax: expect '}'".into(),
         range: (14, 14),
@@ -47,7 +43,7 @@ pub fn parse_4() {
 
 #[test]
 pub fn parse_5() {
-    let error = HEADER.parse("{#if x}{:else}{:else}{/if}").unwrap_err();
+    let error = header().parse("{#if x}{:else}{:else}{/if}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "'else' cannot come after 'else'".into(),
         range: (16, 20),
@@ -56,7 +52,7 @@ pub fn parse_5() {
 
 #[test]
 pub fn parse_6() {
-    let error = HEADER.parse("{#if x}{:elif}{:else}{/if}").unwrap_err();
+    let error = header().parse("{#if x}{:elif}{:else}{/if}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "invalid syntax for directive 'elif': expect expression".into(),
         range: (13, 13),
@@ -65,7 +61,7 @@ pub fn parse_6() {
 
 #[test]
 pub fn parse_7() {
-    let error = HEADER.parse("{#if x}{:else}{:elif x}{/if}").unwrap_err();
+    let error = header().parse("{#if x}{:else}{:elif x}{/if}").unwrap_err();
     assert_eq!(error, SyntaxError {
         message: "'elif' cannot come after 'else'".into(),
         range: (16, 20),
@@ -75,7 +71,7 @@ pub fn parse_7() {
 #[test]
 pub fn render_1() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#if true}
             Hello
         {:else}
@@ -88,7 +84,7 @@ pub fn render_1() {
 #[test]
 pub fn render_2() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#if false}
             Hello
         {:else}
@@ -101,7 +97,7 @@ pub fn render_2() {
 #[test]
 pub fn render_3() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#if false}
             Hello
         {:elif true}
@@ -116,7 +112,7 @@ pub fn render_3() {
 #[test]
 pub fn render_4() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#if false}
             Hello
         {:elif false}
@@ -131,7 +127,7 @@ pub fn render_4() {
 #[test]
 pub fn render_5() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#if false}
             Hello
         {:elif false}
@@ -1,16 +1,15 @@
 use dyn_std::Instance;
-use once_cell::sync::Lazy;
 use yfelo::builtin::Stub;
-use yfelo::default::{Expr, Language, Pattern};
-use yfelo::{Element, Header, Node, SyntaxError, Yfelo};
+use yfelo::default::{BinaryOp, Expr, Language, Pattern};
+use yfelo::{Element, Header, Node, ParseError, SyntaxError, Yfelo};
 
-const HEADER: Lazy<Header> = Lazy::new(|| {
+fn header() -> Header<'static, 'static> {
     let yfelo = Box::leak(Box::new(Yfelo::new()));
     yfelo.add_directive::<Stub>("foo");
     yfelo.add_directive::<Stub>("bar");
     yfelo.add_language::<Expr, Pattern, Language>("default");
     yfelo.prepare("{@yfelo}", false).unwrap()
-});
+}
 
 macro_rules! ident {
     ($v:expr, $range:tt $(,)?) => {
@@ -20,7 +19,7 @@ macro_rules! ident {
 
 macro_rules! apply {
     ($lhs:expr, $rhs:expr, $range:tt $(,)?) => {
-        Expr::Apply(Box::from($lhs), vec![$rhs], Some($range))
+        Expr::Apply(Box::from($lhs), vec![(None, $rhs)], Some($range))
     };
 }
 
@@ -32,7 +31,7 @@ macro_rules! index {
 
 #[test]
 pub fn basic_1() {
-    let nodes = HEADER.parse("(Hello) {world}!").unwrap();
+    let nodes = header().parse("(Hello) {world}!").unwrap();
     assert_eq!(nodes, vec![
         Node::Text("(Hello) ".into()),
         Node::Expr(Box::from(Instance::new(ident!("world", (9, 14))))),
@@ -42,7 +41,7 @@ pub fn basic_1() {
 
 #[test]
 pub fn basic_2() {
-    let nodes = HEADER.parse("{world}").unwrap();
+    let nodes = header().parse("{world}").unwrap();
     assert_eq!(nodes, vec![
         Node::Expr(Box::from(Instance::new(ident!("world", (1, 6))))),
     ]);
@@ -50,7 +49,7 @@ pub fn basic_2() {
 
 #[test]
 pub fn basic_3() {
-    let nodes = HEADER.parse("{w(or[ld])}").unwrap();
+    let nodes = header().parse("{w(or[ld])}").unwrap();
     assert_eq!(nodes, vec![
         Node::Expr(Box::from(Instance::new(apply!(
             ident!("w", (1, 2)),
@@ -62,7 +61,7 @@ pub fn basic_3() {
 
 #[test]
 pub fn basic_4() {
-    let nodes = HEADER.parse("{{w:or,ld}}!").unwrap();
+    let nodes = header().parse("{{w:or,ld}}!").unwrap();
     assert_eq!(nodes, vec![
         Node::Expr(Box::from(Instance::new(Expr::Object(vec![
             (ident!("w", (2, 3)), Some(ident!("or", (4, 6)))),
@@ -72,9 +71,170 @@ pub fn basic_4() {
     ]);
 }
 
+#[test]
+pub fn lambda_1() {
+    let nodes = header().parse("{x => x}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::Lambda(
+            vec![(Pattern::Ident("x".into(), Some((1, 2))), None)],
+            Box::new(ident!("x", (6, 7))),
+            Some((1, 7)),
+        )))),
+    ]);
+}
+
+#[test]
+pub fn lambda_2() {
+    let nodes = header().parse("{(x, y = z) => (x)}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::Lambda(
+            vec![
+                (Pattern::Ident("x".into(), Some((2, 3))), None),
+                (Pattern::Ident("y".into(), Some((5, 6))), Some(ident!("z", (9, 10)))),
+            ],
+            Box::new(ident!("x", (16, 17))),
+            Some((1, 18)),
+        )))),
+    ]);
+}
+
+#[test]
+pub fn spread_1() {
+    let nodes = header().parse("{[...x, y]}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::Array(vec![
+            Expr::Spread(Box::new(ident!("x", (5, 6))), Some((2, 6))),
+            ident!("y", (8, 9)),
+        ], Some((1, 10)))))),
+    ]);
+}
+
+#[test]
+pub fn optional_1() {
+    let nodes = header().parse("{a?.b}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(index!(
+            Expr::Optional(Box::new(ident!("a", (1, 2))), Some((2, 4))),
+            Expr::String("b".into(), Some((4, 5))),
+            false,
+            (2, 5),
+        )))),
+    ]);
+}
+
+#[test]
+pub fn template_1() {
+    let nodes = header().parse("{`a${ b }`}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::Template(vec![
+            Expr::String("a".into(), Some((2, 3))),
+            ident!("b", (6, 7)),
+        ], Some((1, 10)))))),
+    ]);
+}
+
+#[test]
+pub fn escape_1() {
+    let nodes = header().parse(r#"{'\x41\u0042\u{1F600}\0\'\\\`'}"#).unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::String("AB\u{1F600}\0'\\`".into(), Some((1, 30)))))),
+    ]);
+}
+
+#[test]
+pub fn escape_2() {
+    let err = header().parse(r#"{x + 'a\qb'}"#).unwrap_err();
+    assert_eq!(err, SyntaxError {
+        message: "invalid escape sequence '\\q'".into(),
+        range: (5, 11),
+    });
+    let err = header().parse(r#"{`${x}\u{110000}`}"#).unwrap_err();
+    assert_eq!(err, SyntaxError {
+        message: "invalid unicode escape sequence".into(),
+        range: (6, 16),
+    });
+    let err = header().parse(r#"{'\012'}"#).unwrap_err();
+    assert_eq!(err, SyntaxError {
+        message: "octal escape sequences are not supported".into(),
+        range: (1, 7),
+    });
+}
+
+#[test]
+pub fn literal_1() {
+    let err = header().parse("{0x8000_0000_0000_0000}").unwrap_err();
+    assert_eq!(err, SyntaxError {
+        message: "integer literal out of range".into(),
+        range: (1, 22),
+    });
+    let err = header().parse("{1 + 1e999}").unwrap_err();
+    assert_eq!(err, SyntaxError {
+        message: "number literal out of range".into(),
+        range: (5, 10),
+    });
+}
+
+#[test]
+pub fn slice_1() {
+    let nodes = header().parse("{a[:b]}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::Slice(
+            Box::new(ident!("a", (1, 2))),
+            None,
+            Some(Box::new(ident!("b", (4, 5)))),
+            Some((2, 6)),
+        )))),
+    ]);
+}
+
+#[test]
+pub fn object_1() {
+    let nodes = header().parse("{{[k]: v}}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::Object(vec![
+            (Expr::Computed(Box::new(ident!("k", (3, 4))), Some((3, 4))), Some(ident!("v", (7, 8)))),
+        ], Some((1, 9)))))),
+    ]);
+}
+
+#[test]
+pub fn pattern_1() {
+    for input in ["{a = 1, b: [c, ...d], e: f = 2, ...g}", r#"[{a}, b = "x", ...c]"#] {
+        let (pattern, _) = Pattern::parse(input, 0).unwrap();
+        assert_eq!(pattern.to_string(), input);
+    }
+}
+
+#[test]
+pub fn pattern_2() {
+    for (input, message, range) in [
+        ("[...a, b]", "rest element must be last", (1, 5)),
+        ("{...a, b}", "rest element must be last", (1, 5)),
+        ("[...a = []]", "rest element cannot have a default value", (8, 10)),
+        ("{a: ...b}", "unexpected rest pattern '...b'", (4, 8)),
+    ] {
+        let error = Pattern::parse(input, 0).unwrap_err();
+        assert_eq!(error, ParseError::Invalid(SyntaxError { message: message.into(), range }));
+    }
+    assert!(matches!(Pattern::parse("1", 0), Err(ParseError::Incomplete(_))));
+}
+
+#[test]
+pub fn membership_1() {
+    let nodes = header().parse("{a not in inner}").unwrap();
+    assert_eq!(nodes, vec![
+        Node::Expr(Box::from(Instance::new(Expr::Binary(
+            Box::new(ident!("a", (1, 2))),
+            BinaryOp::NotIn,
+            Box::new(ident!("inner", (10, 15))),
+            Some((3, 9)),
+        )))),
+    ]);
+}
+
 #[test]
 pub fn invalid_tag_1() {
-    let err = HEADER.parse("{Hello} {world").unwrap_err();
+    let err = header().parse("{Hello} {world").unwrap_err();
     assert_eq!(err, SyntaxError {
         message: "invalid tag syntax: expect '}'".into(),
         range: (14, 14),
@@ -83,7 +243,7 @@ pub fn invalid_tag_1() {
 
 #[test]
 pub fn invalid_tag_2() {
-    let err = HEADER.parse("{Hel(lo}").unwrap_err();
+    let err = header().parse("{Hel(lo}").unwrap_err();
     assert_eq!(err, SyntaxError {
         message: "invalid tag syntax: expect '}'".into(),
         range: (4, 4),
@@ -92,7 +252,7 @@ pub fn invalid_tag_2() {
 
 #[test]
 pub fn invalid_tag_3() {
-    let err = HEADER.parse("{Hel)lo}").unwrap_err();
+    let err = header().parse("{Hel)lo}").unwrap_err();
     assert_eq!(err, SyntaxError {
         message: "invalid tag syntax: expect '}'".into(),
         range: (4, 4),
@@ -101,7 +261,7 @@ pub fn invalid_tag_3() {
 
 #[test]
 pub fn invalid_tag_4() {
-    let err = HEADER.parse("{H(e[l)l]o}").unwrap_err();
+    let err = header().parse("{H(e[l)l]o}").unwrap_err();
     assert_eq!(err, SyntaxError {
         message: "invalid tag syntax: expect '}'".into(),
         range: (2, 2),
@@ -110,7 +270,7 @@ pub fn invalid_tag_4() {
 
 #[test]
 pub fn tag_1() {
-    let nodes = HEADER.parse("{#foo}Hello{/foo} {#bar}world{/bar}!").unwrap();
+    let nodes = header().parse("{#foo}Hello{/foo} {#bar}world{/bar}!").unwrap();
     assert_eq!(nodes, vec![
         Node::Element(Element {
             directive: Box::new(Instance::new(Stub)),
@@ -129,7 +289,7 @@ pub fn tag_1() {
 
 #[test]
 pub fn tag_2() {
-    let nodes = HEADER.parse("{#foo}Hello{@bar} {#bar}world{/bar}!{/foo}").unwrap();
+    let nodes = header().parse("{#foo}Hello{@bar} {#bar}world{/bar}!{/foo}").unwrap();
     assert_eq!(nodes, vec![
         Node::Element(Element {
             directive: Box::new(Instance::new(Stub)),
@@ -155,21 +315,21 @@ pub fn tag_2() {
 
 #[test]
 pub fn unmatched_tag_1() {
-    let error = HEADER.parse("{#foo}Hello {#bar}world{/foo}!{/bar}").unwrap_err();
+    let error = header().parse("{#foo}Hello {#bar}world{/foo}!{/bar}").unwrap_err();
     assert_eq!(error.message, "unmatched tag name: expect 'bar', found 'foo'");
     assert_eq!(error.range, (25, 28));
 }
 
 #[test]
 pub fn unmatched_tag_2() {
-    let error = HEADER.parse("{#foo}Hello{/foo} world{/bar}!").unwrap_err();
+    let error = header().parse("{#foo}Hello{/foo} world{/bar}!").unwrap_err();
     assert_eq!(error.message, "unmatched tag name 'bar'");
     assert_eq!(error.range, (25, 28));
 }
 
 #[test]
 pub fn unmatched_tag_3() {
-    let error = HEADER.parse("{#foo}Hello{/foo} world{#bar}!").unwrap_err();
+    let error = header().parse("{#foo}Hello{/foo} world{#bar}!").unwrap_err();
     assert_eq!(error.message, "unmatched tag name 'bar'");
     assert_eq!(error.range, (25, 28));
 }
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{header, runtime_error};
+use dyn_std::Instance;
+use yfelo::SyntaxError;
+use yfelo::default::Context;
+
+#[test]
+pub fn parse_1() {
+    let error = header().parse("{#set x = 1}{/set}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "directive 'set' should be empty".into(),
+        range: (2, 5),
+    });
+}
+
+#[test]
+pub fn parse_2() {
+    let error = header().parse("{@set x}").unwrap_err();
+    assert_eq!(error, SyntaxError {
+        message: "invalid syntax for directive 'set': expected punctuation '='".into(),
+        range: (7, 7),
+    });
+}
+
+#[test]
+pub fn render_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def total = 0}
+        {#for x in [1, 2, 3]}
+            {@set total = total + x}
+        {/for}
+        {total}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "6");
+}
+
+#[test]
+pub fn render_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def a = 1}
+        {@def b = 2}
+        {@set [a, b] = [b, a]}
+        {a}{b}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "21");
+}
+
+#[test]
+pub fn render_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {@set x = 1}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "'x' is not defined");
+}
+
+#[test]
+pub fn render_4() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {@def x = 1}
+        {@def x = 2}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "'x' is already defined");
+}
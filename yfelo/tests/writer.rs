@@ -1,18 +1,16 @@
-use dyn_std::Instance;
-use once_cell::sync::Lazy;
-use yfelo::{Header, Yfelo};
-use yfelo::default::{Context, Expr, Language, Pattern};
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
 
-const HEADER: Lazy<Header> = Lazy::new(|| {
-    let yfelo = Box::leak(Box::new(Yfelo::new()));
-    yfelo.add_language::<Expr, Pattern, Language>("default");
-    yfelo.prepare("{@yfelo}", false).unwrap()
-});
+use common::{header, runtime_error};
+use dyn_std::Instance;
+use yfelo::default::{Config, Context, DateTime, NumberFormat, RuntimeError, Undefined, MAX_TIMESTAMP};
 
 #[test]
 pub fn basic_1() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {@def world = 'yfelo'}
         Hello, {world}!
     ", ctx.as_mut()).unwrap();
@@ -22,7 +20,7 @@ pub fn basic_1() {
 #[test]
 pub fn def_inline_1() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {@def foo = 'bar'}
         {@def bar = 'foo'}
         {foo + bar}
@@ -33,7 +31,7 @@ pub fn def_inline_1() {
 #[test]
 pub fn def_block_1() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#def text}
             Hello, world!
         {/def}
@@ -45,7 +43,7 @@ pub fn def_block_1() {
 #[test]
 pub fn def_block_2() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#def text(world)}
             Hello, {world}!
         {/def}
@@ -57,7 +55,7 @@ pub fn def_block_2() {
 #[test]
 pub fn def_default_1() {
     let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
-    let output = HEADER.render("
+    let output = header().render("
         {#def text(world = 'yfelo')}
             Hello, {world}!
         {/def}
@@ -65,3 +63,739 @@ pub fn def_default_1() {
     ", ctx.as_mut()).unwrap();
     assert_eq!(output, "Hello, yfelo!");
 }
+
+#[test]
+pub fn def_scope_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def x = 'outer'}
+        {#def text}
+            {x}
+        {/def}
+        {#for x in ['inner']}
+            {@apply text}
+        {/for}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "outer");
+}
+
+#[test]
+pub fn def_scope_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#for x in [1, 2]}
+            {#def text(y = x * 2)}
+                {x}{y}
+            {/def}
+            {@apply text}
+        {/for}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "1224");
+}
+
+#[test]
+pub fn def_scope_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#def count(n)}
+            {#if n > 0}
+                {@apply count(n - 1)}{n}
+            {/if}
+        {/def}
+        {@apply count(3)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "123");
+}
+
+#[test]
+pub fn def_scope_4() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def make = n => (x => x + n)}
+        {@def add2 = make(2)}
+        {make(2)(3)} {add2(1)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "5 3");
+}
+
+#[test]
+pub fn def_scope_5() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def fs = []}
+        {#for x in [1, 2]}
+            {#def text}
+                {x}
+            {/def}
+            {@set fs = [...fs, y => x * y, text]}
+        {/for}
+        {fs[0](10)} {fs[2](10)} {@apply fs[3]}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "10 20 2");
+}
+
+#[test]
+pub fn lambda_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def double = x => x * 2}
+        {@def add = (a, b = 1) => a + b}
+        {double(3)} {add(2)} {add(2, 3)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "6 3 5");
+}
+
+#[test]
+pub fn lambda_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def n = 10}
+        {#def twice(f, x)}
+            {f(f(x))}
+        {/def}
+        {@apply twice(x => x + n, 1)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "21");
+}
+
+#[test]
+pub fn lambda_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def fs = [1, 2].map(n => (x => x + n))}
+        {@def compose = (f, g) => (x => g(f(x)))}
+        {fs[0](10)} {fs[1](10)} {compose(fs[0], fs[1])(10)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "11 12 13");
+}
+
+#[test]
+pub fn spread_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def xs = [2, 3]}
+        {@def obj = {a: 1, b: 2}}
+        {[1, ...xs, 4, ...[]]} {{...obj, b: 3, c: 4}} {{c: 0, ...obj}}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[1, 2, 3, 4] {a: 1, b: 3, c: 4} {a: 1, b: 2, c: 0}");
+}
+
+#[test]
+pub fn pattern_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def obj = {a: 1, b: [2, 3, 4], c: 5, d: 6}}
+        {@def {a, b: [x, ...y], ...z} = obj}
+        {a} {x} {y} {z}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "1 2 [3, 4] {c: 5, d: 6}");
+}
+
+#[test]
+pub fn pattern_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def {a = 1, b: c = a + 1, 'd': [e = 3, f = 4]} = {a: 0, d: [5]}}
+        {a} {c} {e} {f}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "0 1 5 4");
+}
+
+#[test]
+pub fn pattern_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {#for {name, price = 0} in [{name: 'a', price: 1}, {name: 'b'}]}
+            {name}={price};
+        {/for}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "a=1;b=0;");
+}
+
+#[test]
+pub fn cond_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def n = 2}
+        {n > 1 ? 'many' : 'one'} {n > 2 ? 'a' : n > 1 ? 'b' : 'c'} {{x: n ? 1 : 0}}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "many b {x: 1}");
+}
+
+#[test]
+pub fn nullish_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def user = {name: null, age: 0}}
+        {user.name ?? 'anonymous'} {user.age ?? 18} {null ?? null ?? 'x'} {1 ?? user.a.b}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "anonymous 0 x 1");
+}
+
+#[test]
+pub fn optional_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def a = {b: {c: 1}, f: x => x + 1}}
+        {@def k = 'b'}
+        [{a?.b?.c}] [{a.x?.y.z}] [{a?.[k].c}] [{a.f?.(1)}] [{a.g?.(1)}] [{null?.b ?? 2}]
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[1] [] [1] [2] [] [2]");
+}
+
+#[test]
+pub fn logical_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def a = null}
+        {a && a.b.c} {a || 'x'} {1 && 'x'} {!a || a.b.c}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "false true true true");
+}
+
+#[test]
+pub fn logical_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        logical_operand: true,
+        ..Default::default()
+    })));
+    let output = header().render("
+        {@def title = ''}
+        [{title || 'Untitled'}] [{'x' || 'y'}] [{title && title.a.b}] [{1 && 'x'}]
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[Untitled] [x] [] [x]");
+}
+
+#[test]
+pub fn undefined_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        undefined: Undefined::Error,
+        ..Default::default()
+    })));
+    let error = header().render("
+        {usre.name}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "'usre' is not defined at 10..14");
+    let error = header().render("
+        {@def user = { name: 'yfelo' }}
+        {user.nmae}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "'nmae' is not defined at 54..59");
+    let error = header().render("
+        {@def post = { tags: ['a'] }}
+        {post.tags[1]}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "index 1 is out of range at 57..60");
+}
+
+#[test]
+pub fn undefined_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        undefined: Undefined::Error,
+        ..Default::default()
+    })));
+    let output = header().render("
+        {@def user = { name: null, tags: [] }}
+        [{user.name}] [{user?.age}] [{user.age ?? 18}] [{guest ?? 'guest'}] [{guest?.name}] [{user.tags?.[0]}] [{'ab'[2] ?? 'none'}]
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[] [] [18] [guest] [] [] [none]");
+}
+
+#[test]
+pub fn undefined_3() {
+    let warnings = Rc::new(RefCell::new(vec![]));
+    let hook = warnings.clone();
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        undefined: Undefined::Warn(Rc::new(move |error: &RuntimeError| hook.borrow_mut().push(error.message.clone()))),
+        ..Default::default()
+    })));
+    let output = header().render("
+        [{usre}] [{user ?? 'none'}]
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[] [none]");
+    assert_eq!(*warnings.borrow(), vec!["'usre' is not defined at 11..15".to_string()]);
+}
+
+#[test]
+pub fn integer_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {9007199254740993 + 1} {7 / 2} {7 % 3} {2 ** 62} {2 ** -1} {1 + 0.5} {1 << 4 | 1} {1 == 1.0}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "9007199254740994 3.5 1 4611686018427387904 0.5 1.5 17 true");
+}
+
+#[test]
+pub fn literal_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {1e6} {2.5E-3} {.5} {0x1F} {0o17} {0b1010} {1_000_000} {0xFF_FF} {1_0.2_5e1_0}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "1000000 0.0025 0.5 31 15 10 1000000 65535 102500000000");
+}
+
+#[test]
+pub fn integer_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {9223372036854775807 + 1}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "integer overflow");
+    for expr in ["3 << 62", "1 << 63", "1 << 64", "-1 << 64"] {
+        let error = header().render(&format!("{{{}}}", expr), ctx.as_mut()).unwrap_err();
+        assert_eq!(runtime_error(error).message, "integer overflow");
+    }
+    let output = header().render("{1 << 62} {-1 << 63} {-3 << 61}", ctx.as_mut()).unwrap();
+    assert_eq!(output, "4611686018427387904 -9223372036854775808 -6917529027641081856");
+}
+
+#[test]
+pub fn integer_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {1.5 & 1}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect integer, found number");
+}
+
+#[test]
+pub fn integer_4() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    for input in ["{7 / 0}", "{7 % 0}"] {
+        let error = header().render(input, ctx.as_mut()).unwrap_err();
+        assert_eq!(runtime_error(error).message, "division by zero");
+    }
+    let output = header().render("{7 / 0.0} {-7.0 / 0} {0.0 / 0 == 0.0 / 0}", ctx.as_mut()).unwrap();
+    assert_eq!(output, "inf -inf false");
+}
+
+#[test]
+pub fn template_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def id = 42}
+        {@def url = `/users/${id}/posts`}
+        {url} {`${ id + 1 }${''}-${null}-${[1, 2]}`} {`a\\`b\\${c}`} {`outer ${`inner ${id}`}`}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "/users/42/posts 43--[1, 2] a`b${c} outer inner 42");
+}
+
+#[test]
+pub fn slice_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def xs = [1, 2, 3, 4]}
+        {xs[1:3]} {xs[:-1]} {xs[-2:]} {xs[:]} {xs[3:1]} {xs[-10:10]} {xs[-1]} {xs[-5] ?? 'none'} {xs[4] ?? 'none'}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[2, 3] [1, 2, 3] [3, 4] [1, 2, 3, 4] [] [1, 2, 3, 4] 4 none none");
+}
+
+#[test]
+pub fn slice_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def s = 'héllo'}
+        {s[1]} {s[-1]} {s[1:3]} {s[:-1]} {s[9] ?? 'none'} {[s[2:2]]} {(null)?.[1:]}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "é o él héll none [] ");
+    let error = header().render("
+        {{ a: 1 }[0:1]}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "cannot slice object");
+    let error = header().render("
+        {s['a']}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "cannot index into string with string");
+    let error = header().render("
+        {s[1.5]}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect integer, found number");
+}
+
+#[test]
+pub fn method_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def name = ' Yfelo '}
+        {name.trim().upper()} {name.len()} {'a,b,c'.split(',')} {'a b  c'.split()} {'héllo'.index_of('l')}
+        {'abc'.starts_with('ab')} {'abc'.ends_with('b')} {'a-b'.replace('-', '+')} {'ab'.repeat(2)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "YFELO 7 [a, b, c] [a, b, c] 2true false a+b abab");
+}
+
+#[test]
+pub fn method_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def items = [3, 1, 2]}
+        {items.len()} {items.join(', ')} {items.reverse()} {items.index_of(2)} {items.map(x => x * 2)}
+        {items.filter(x => x > 1)} {items.find(x => x < 3)} {items.find(x => x > 3) ?? 'none'}
+        {@def obj = { b: 2, a: 1, len: () => 'own' }}
+        {obj.keys()} {obj.values().len()} {{ a: 1 }.entries()} {obj.len()}
+        {(-3).abs()} {2.5.floor()} {2.1.ceil()} {2.5.round()} {3.14159.round(2)} {7.round()}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "3 3, 1, 2 [2, 1, 3] 2 [6, 2, 4][3, 2] 1 none[a, b, len] 3 [[a, 1]] own3 2 3 3 3.14 7");
+}
+
+#[test]
+pub fn method_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {'abc'.reverse()}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "string has no method 'reverse'");
+    let error = header().render("
+        {[1].join(sep = ',')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "unknown argument 'sep'");
+    let error = header().render("
+        {[1, 2].sort(true, 1)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect 0 to 1 arguments, found 2");
+    let error = header().render("
+        {'abc'.upper(1)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect 0 arguments, found 1");
+    let error = header().render("
+        {'ab'.repeat(4611686018427387904)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "repeated string too long");
+    let output = header().render("
+        {@def user = null}
+        {user?.name.upper() ?? 'anonymous'}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "anonymous");
+}
+
+#[test]
+pub fn object_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def k = 'b'}
+        {@def a = 1}
+        {{ a, [k]: 2, ['c' + k]: 3, 1: 'x', 2.5: 'y', 'd e': 4, }}
+        {{ ...{ a: 0, k: 1 }, a, nested: { list: [1, [2, 3,],], }, }}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "{1: x, 2.5: y, a: 1, b: 2, cb: 3, d e: 4}{a: 1, k: 1, nested: {list: [1, [2, 3]]}}");
+}
+
+#[test]
+pub fn object_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def k = 'b'}
+        {@def { [k]: x, k: y, 1: z } = { b: 'computed', k: 'literal', 1: 'numeric' }}
+        {x} {y} {z}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "computed literal numeric");
+    let error = header().render("
+        {{ [true]: 1 }}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect string, found bool");
+}
+
+#[test]
+pub fn collection_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def items = [
+            { name: 'pen', category: 'office', price: 3 },
+            { name: 'apple', category: 'food', price: 2 },
+            { name: 'desk', category: 'office', price: 120 },
+            { name: 'pear', category: 'food', price: 2 },
+        ]}
+        {items.sort_by('price', true).map(x => x.name)}
+        {items.sort_by(x => x.price).map(x => x.name)}
+        {#for group, category in items.group_by('category')}
+            {category}: {group.sum('price')} / {group.count()};
+        {/for}
+        {items.avg('price')} {items.count(x => x.price < 10)} {items.unique('category').len()}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[desk, pen, apple, pear][apple, pear, pen, desk]food: 4 / 2;office: 123 / 2;31.75 3 2");
+}
+
+#[test]
+pub fn collection_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {[3, 'b', null, 1.5, 'a', [1], true].sort()} {[2, 1, 2, 3, 1].unique()} {[1, 2].sum()} {[0.5, 1].sum()} {[].sum()} {[].avg() ?? 'none'}
+        {[1, [2, [3, [4]]]].flatten()} {[1, [2, [3, [4]]]].flatten(5)} {[1, 2, 3].zip(['a', 'b'])} {[1, 2, 3, 4, 5].chunk(2)}
+        {{ a: 1, b: 2, c: 3 }.filter(x => x % 2).map(x => x * 10)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "[null, true, 1.5, 3, a, b, [1]] [2, 1, 3] 3 1.5 0 none[1, 2, [3, [4]]] [1, 2, 3, 4] [[1, a], [2, b]] [[1, 2], [3, 4], [5]]{a: 10, c: 30}");
+    let error = header().render("
+        {[1, 'a'].sum()}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect number, found string");
+    let output = header().render("
+        {@def stock = { pen: 3, desk: 1, lamp: 0 }}
+        {stock.sum()} {stock.avg()} {stock.count(n => n > 0)} {{}.sum()}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "4 1.3333333333333333 2 0");
+    let error = header().render("
+        {{ a: 1 }.group_by('a')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "object has no method 'group_by'");
+}
+
+#[test]
+pub fn date_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def d = date('2024-02-29T13:05:09.250+08:00')}
+        {d} | {d.format('%Y-%m-%d %H:%M:%S.%f %z %a %b %j %I%p %%')} | {d.year()}/{d.month()}/{d.day()} {d.weekday()}
+        | {date(0)} {date(0, 330)} {date(0, '-01:30')} {date('1969-12-31 23:59:59').timestamp()}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "2024-02-29T13:05:09.250+08:00 | 2024-02-29 13:05:09.250 +0800 Thu Feb 060 01PM % | 2024/2/29 4\
+        | 1970-01-01T00:00:00Z 1970-01-01T05:30:00+05:30 1969-12-31T22:30:00-01:30 -1000");
+}
+
+#[test]
+pub fn date_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def d = date('2024-02-29')}
+        {d.add(1)} {d.add(-2, 'hours')} {d.add(1.5, 'weeks').format('%F %T')}
+        {date('2024-03-01').diff(d)} {date('2024-03-01').diff(d, 'hours')} {d.diff(date('2024-03-01'))}
+        {date('2024-01-01T00:00:00Z').to_offset('-05:00')}
+        {date('2024-01-01T08:00:00+08:00') == date('2024-01-01')} {date('2024-01-01') < date('2023-12-31T23:00:00-02:00')}
+        {[date('2024-01-02'), date('2024-01-01')].sort().map(x => x.day())}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "2024-03-01T00:00:00Z 2024-02-28T22:00:00Z 2024-03-10 12:00:00\
+        1 24 -1\
+        2023-12-31T19:00:00-05:00\
+        true true\
+        [1, 2]");
+}
+
+#[test]
+pub fn date_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {date('2023-02-29')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "invalid date '2023-02-29'");
+    let error = header().render("
+        {date('2023-02-28').format('%Q')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "invalid date format '%Q'");
+    let error = header().render("
+        {date('2023-02-28').add(1, 'fortnights')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "invalid time unit 'fortnights'");
+    let error = header().render("
+        {date(0, -2147483648)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "invalid offset '-2147483648'");
+}
+
+#[test]
+pub fn date_4() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {date(8640000000000000, 60).year()} {date(-8640000000000000, -60).year()}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "275760 -271821");
+    for expr in [
+        "date(9223372036854775807, 60)",
+        "date(-9223372036854775807, -60).year()",
+        "date(8640000000000001)",
+        "date(8640000000000000).add(1, 'ms')",
+        "date(-8640000000000000).add(-1e300)",
+    ] {
+        let error = header().render(&format!("{{{}}}", expr), ctx.as_mut()).unwrap_err();
+        assert_eq!(runtime_error(error).message, "date out of range");
+    }
+}
+
+#[test]
+pub fn date_5() {
+    for (date, output) in [
+        (DateTime::new(-MAX_TIMESTAMP, 0).unwrap(), "-271821-04-20T00:00:00Z"),
+        (DateTime::new(MAX_TIMESTAMP, 60).unwrap(), "+275760-09-13T01:00:00+01:00"),
+        (DateTime::parse("-0001-12-31").unwrap(), "-0001-12-31T00:00:00Z"),
+        (DateTime::parse("+10000-01-01").unwrap(), "+10000-01-01T00:00:00Z"),
+    ] {
+        assert_eq!(date.to_string(), output);
+        assert_eq!(DateTime::parse(output).unwrap(), date);
+    }
+    assert!(DateTime::parse("10000-01-01").is_err());
+    assert!(DateTime::parse("+1000000-01-01").is_err());
+}
+
+#[test]
+pub fn regex_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def s = 'order-42, order-7'}
+        {s.matches('order-\\\\d+')} {'abc'.matches('^b')} {s.captures('(\\\\w+)-([0-9]+)')} {s.captures('x(y)?') ?? 'none'}
+        {s.regex_replace('order-([0-9]+)', '#$1')} {s.regex_replace('(?P<n>[0-9]+)', '<${n}>')}
+        {s.regex_replace('[a-z]+', m => m[0].upper())} {'a1b22c'.regex_split('[0-9]+')}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "true false [order-42, order, 42] none#42, #7 order-<42>, order-<7>ORDER-42, ORDER-7 [a, b, c]");
+}
+
+#[test]
+pub fn regex_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {'abc'.matches('(')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "invalid regex '(': unclosed group");
+}
+
+#[test]
+pub fn compare_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {'apple' < 'banana'} {'b' >= 'a'} {[1, 2] < [1, 3]} {[1] < [1, 0]} {['b'] > ['a', 'z']}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "true true true true true");
+}
+
+#[test]
+pub fn compare_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {1 < 'a'}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "cannot compare number and string");
+}
+
+#[test]
+pub fn equality_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def tags = ['a']}
+        {tags == ['a']} {[1, [2, 'b']] == [1.0, [2, 'b']]} {{ a: 1, b: [null] } == { b: [null], a: 1 }} {[1] == [1, 2]} {{ a: 1 } == { a: '1' }}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "true true true false false");
+}
+
+#[test]
+pub fn equality_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def f = x => x}
+        {@def nan = 0.0 / 0}
+        {f == f} {f == (x => x)} {nan == nan} {[nan] != [nan]}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "true false false true");
+}
+
+#[test]
+pub fn membership_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def user = { roles: ['admin', 'editor'] }}
+        {'admin' in user.roles} {'guest' not in user.roles} {'k' in { k: 1 }} {'ell' in 'hello'} {1 in [1.0]}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "true true true true true");
+}
+
+#[test]
+pub fn membership_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {1 in 2}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect array, object or string, found number");
+}
+
+#[test]
+pub fn format_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        number: NumberFormat {
+            decimals: Some(2),
+            thousands: Some(','),
+            ..Default::default()
+        },
+        ..Default::default()
+    })));
+    let output = header().render("
+        {1234567.891} {-1234.5} {42} {[0.5]} {'total: ' + 1234.5} {`total: ${1234.5}`}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "1,234,567.89 -1,234.50 42 [0.50] total: 1,234.50 total: 1,234.50");
+}
+
+#[test]
+pub fn format_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        number: NumberFormat {
+            decimals: Some(1),
+            width: 6,
+            zero_pad: true,
+            percent: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    })));
+    let output = header().render("
+        {0.256} {-0.05} {1}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "025.6% -05.0% 100.0%");
+}
+
+#[test]
+pub fn format_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        number: NumberFormat {
+            decimals: Some(2),
+            scientific: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    })));
+    let output = header().render("
+        {1500} {0.000123}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "1.50e3 1.23e-4");
+}
+
+#[test]
+pub fn format_4() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::with_config(Config {
+        number: NumberFormat {
+            decimals: Some(70000),
+            ..Default::default()
+        },
+        ..Default::default()
+    })));
+    let output = header().render("{0.5}", ctx.as_mut()).unwrap();
+    assert_eq!(output, format!("0.5{}", "0".repeat(NumberFormat::MAX_DECIMALS - 1)));
+}
+
+#[test]
+pub fn format_number_1() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {format_number(1234567)} {format_number(1234.5, 2)} {format_number(1234567, 0, ',')} {format_number(-9876.54321, 3, ' ')}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "1234567 1234.50 1,234,567 -9 876.543");
+}
+
+#[test]
+pub fn format_number_2() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let error = header().render("
+        {format_number()}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect 1 to 3 arguments, found 0");
+    let error = header().render("
+        {format_number(1, 2, '::')}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "expect a single character, found ::");
+    let error = header().render("
+        {format_number(1, 70000)}
+    ", ctx.as_mut()).unwrap_err();
+    assert_eq!(runtime_error(error).message, "decimals out of range");
+}
+
+#[test]
+pub fn format_number_3() {
+    let mut ctx: Box<dyn yfelo::Context> = Box::new(Instance::new(Context::new()));
+    let output = header().render("
+        {@def format_number = n => n + 1}{format_number(1)}
+    ", ctx.as_mut()).unwrap();
+    assert_eq!(output, "2");
+}